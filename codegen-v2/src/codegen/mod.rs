@@ -4,6 +4,8 @@
 
 pub mod cpp;
 pub mod proto;
+pub mod python;
 pub mod rust;
 pub mod swift;
+pub mod target;
 pub mod template_generator;
@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::codegen::target::{render_file_info, CodegenTarget, GeneratedStrings};
+use crate::manifest::{EnumInfo, FileInfo, ParamInfo, StructInfo, TypeInfo, TypeVariant};
+use crate::Result;
+use handlebars::Handlebars;
+use heck::ToSnakeCase;
+
+const CLASS_TEMPLATE: &str = include_str!("templates/class.hbs");
+const ENUM_TEMPLATE: &str = include_str!("templates/enum.hbs");
+
+/// Represents a Python wrapper class of a C struct.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonClass {
+    name: String,
+    methods: Vec<PythonMethod>,
+    // The C symbol releasing the underlying object, if any.
+    deinit: Option<String>,
+    // The other wrapper classes and enums referenced by the methods, each
+    // imported from its own module.
+    imports: Vec<String>,
+    // Whether the methods pass or return strings (`TWString`), which require
+    // the conversion helpers.
+    uses_strings: bool,
+    // Whether the methods pass or return data (`TWData`), which require the
+    // conversion helpers.
+    uses_data: bool,
+}
+
+/// Represents a method of a Python wrapper class, calling into the shared
+/// library by the original C symbol name. Properties of the manifest are
+/// rendered as `@property` methods.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonMethod {
+    name: String,
+    // The original name of the underlying C FFI symbol.
+    c_ffi_name: String,
+    is_static: bool,
+    is_property: bool,
+    params: Vec<PythonParam>,
+    // The `ctypes` types of the C function, including `self`.
+    argtypes: Vec<String>,
+    restype: String,
+    // The arguments passed on to the C function, including `self`.
+    args: Vec<String>,
+    // The strings and data created from the arguments before calling the C
+    // function, released right after.
+    temporaries: Vec<PythonTemporary>,
+    // The wrapper class or enum of the result, or the helper converting (and
+    // releasing) the returned string or data, if any.
+    wrap_result: Option<String>,
+}
+
+/// Represents a `TWString` or `TWData` created from a Python argument.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonTemporary {
+    name: String,
+    // E.g. `_string_create(name)`.
+    create: String,
+    // E.g. `_string_delete`.
+    delete: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonParam {
+    name: String,
+    param_type: String,
+}
+
+/// Represents a Python `IntEnum` of a C enum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonEnum {
+    name: String,
+    variants: Vec<PythonEnumVariant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonEnumVariant {
+    name: String,
+    value: String,
+}
+
+/// Maps the manifest type to the Python type, as used in annotations. Custom
+/// types map to their wrapper class or enum, strings and data to `str` and
+/// `bytes`.
+pub fn python_type(ty: &TypeVariant) -> String {
+    match ty {
+        TypeVariant::Struct(name) | TypeVariant::Enum(name) => class_name(name),
+        TypeVariant::String => "str".to_string(),
+        TypeVariant::Data => "bytes".to_string(),
+        _ => ctype(ty),
+    }
+}
+
+/// Maps the manifest type to the `ctypes` type of the C function signature.
+/// Structs, data and strings are passed as opaque pointers and enums as
+/// integers.
+pub fn ctype(ty: &TypeVariant) -> String {
+    let ctype = match ty {
+        TypeVariant::Void => return "None".to_string(),
+        TypeVariant::Bool => "c_bool",
+        TypeVariant::Char => "c_char",
+        TypeVariant::ShortInt => "c_short",
+        TypeVariant::Int => "c_int",
+        TypeVariant::UnsignedInt => "c_uint",
+        TypeVariant::LongInt => "c_long",
+        TypeVariant::Float => "c_float",
+        TypeVariant::Double => "c_double",
+        TypeVariant::SizeT => "c_size_t",
+        TypeVariant::Int8T => "c_int8",
+        TypeVariant::Int16T => "c_int16",
+        TypeVariant::Int32T => "c_int32",
+        TypeVariant::Int64T => "c_int64",
+        TypeVariant::UInt8T => "c_uint8",
+        TypeVariant::UInt16T => "c_uint16",
+        TypeVariant::UInt32T => "c_uint32",
+        TypeVariant::UInt64T => "c_uint64",
+        TypeVariant::Enum(_) => "c_int",
+        TypeVariant::Struct(_) | TypeVariant::Data | TypeVariant::String => "c_void_p",
+    };
+
+    format!("ctypes.{ctype}")
+}
+
+// E.g. `TWPublicKey` -> `PublicKey`.
+fn class_name(name: &str) -> String {
+    name.strip_prefix("TW").unwrap_or(name).to_string()
+}
+
+/// The Python [`CodegenTarget`], rendering one module per struct or enum.
+pub struct PythonTarget<'a> {
+    engine: Handlebars<'a>,
+    file_info: FileInfo,
+}
+
+impl<'a> PythonTarget<'a> {
+    pub fn new(file_info: FileInfo) -> Result<Self> {
+        let mut engine = Handlebars::new();
+        engine.set_strict_mode(true);
+        // Python code must not be HTML-escaped.
+        engine.register_escape_fn(handlebars::no_escape);
+
+        engine.register_template_string("class", CLASS_TEMPLATE)?;
+        engine.register_template_string("enum", ENUM_TEMPLATE)?;
+
+        Ok(PythonTarget { engine, file_info })
+    }
+
+    // Collects the functions and properties associated with the struct, see
+    // `is_member`.
+    fn signatures<'b>(&'b self, strct: &StructInfo) -> Vec<Signature<'b>> {
+        let functions = self
+            .file_info
+            .functions
+            .iter()
+            // `ctypes` can't declare variadic functions.
+            .filter(|func| !func.is_variadic)
+            .map(|func| {
+                let params = func.params.as_slice();
+                (&func.name, func.is_static, false, params, &func.return_type)
+            });
+
+        // Properties take no parameters, besides the instance.
+        let properties = self.file_info.properties.iter().map(|prop| {
+            let params = <&[ParamInfo]>::default();
+            (&prop.name, prop.is_static, true, params, &prop.return_type)
+        });
+
+        functions
+            .chain(properties)
+            .filter(|(name, ..)| self.is_member(name, strct))
+            .collect()
+    }
+
+    // Whether the C symbol belongs to the struct: the symbol starts with the
+    // struct name, followed by an uppercase letter, and no other struct with a
+    // longer name matches. E.g. `TWAnySignerSign` belongs to `TWAnySigner`,
+    // not to `TWAny`.
+    fn is_member(&self, symbol: &str, strct: &StructInfo) -> bool {
+        let has_prefix = |name: &str| {
+            symbol
+                .strip_prefix(name)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| c.is_ascii_uppercase())
+        };
+
+        has_prefix(&strct.name)
+            && !self
+                .file_info
+                .structs
+                .iter()
+                .any(|other| other.name.len() > strct.name.len() && has_prefix(&other.name))
+    }
+
+    fn methods(&self, strct: &StructInfo) -> Vec<PythonMethod> {
+        self.signatures(strct)
+            .into_iter()
+            .map(|(name, is_static, is_property, params, return_type)| {
+                python_method(strct, name, is_static, is_property, params, return_type)
+            })
+            .collect()
+    }
+
+    // Collects the other structs and enums referenced by the parameters and
+    // return types of the methods, sorted by name.
+    fn imports(&self, strct: &StructInfo) -> Vec<String> {
+        let mut imports = vec![];
+
+        for (_, _, _, params, return_type) in self.signatures(strct) {
+            let types = params
+                .iter()
+                .map(|param| &param.ty.variant)
+                .chain(std::iter::once(&return_type.variant));
+
+            for ty in types {
+                match ty {
+                    TypeVariant::Struct(name) | TypeVariant::Enum(name) if *name != strct.name => {
+                        imports.push(class_name(name))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        imports.sort();
+        imports.dedup();
+        imports
+    }
+
+    // Whether the parameters or return types of the methods reference the
+    // given type.
+    fn uses_type(&self, strct: &StructInfo, ty: &TypeVariant) -> bool {
+        self.signatures(strct)
+            .into_iter()
+            .any(|(_, _, _, params, return_type)| {
+                params.iter().any(|param| param.ty.variant == *ty) || return_type.variant == *ty
+            })
+    }
+
+    // Finds the deinit associated with the struct, see `is_member`.
+    fn deinit(&self, strct: &StructInfo) -> Option<String> {
+        self.file_info
+            .deinits
+            .iter()
+            .find(|deinit| self.is_member(&deinit.name, strct))
+            .map(|deinit| deinit.name.clone())
+    }
+}
+
+// The name, `is_static`, `is_property`, the parameters and the return type of
+// a function or property.
+type Signature<'a> = (&'a String, bool, bool, &'a [ParamInfo], &'a TypeInfo);
+
+fn python_method(
+    strct: &StructInfo,
+    c_ffi_name: &str,
+    is_static: bool,
+    is_property: bool,
+    params: &[ParamInfo],
+    return_type: &TypeInfo,
+) -> PythonMethod {
+    let (mut argtypes, mut args) = (vec![], vec![]);
+
+    // The instance is passed on to the C function, assuming the method is not
+    // static.
+    if !is_static {
+        argtypes.push(ctype(&TypeVariant::Struct(strct.name.clone())));
+        args.push("self.raw_value".to_string());
+    }
+
+    let (mut python_params, mut temporaries) = (vec![], vec![]);
+    for param in params {
+        // Skip the self parameter.
+        if matches!(&param.ty.variant, TypeVariant::Struct(name) if *name == strct.name) {
+            continue;
+        }
+
+        let name = param.name.to_snake_case();
+        argtypes.push(ctype(&param.ty.variant));
+        args.push(match &param.ty.variant {
+            TypeVariant::Struct(_) => format!("{name}.raw_value"),
+            TypeVariant::Enum(_) => format!("int({name})"),
+            TypeVariant::String | TypeVariant::Data => {
+                let prefix = helper_prefix(&param.ty.variant);
+                temporaries.push(PythonTemporary {
+                    name: format!("{name}_raw"),
+                    create: format!("{prefix}_create({name})"),
+                    delete: format!("{prefix}_delete"),
+                });
+                format!("{name}_raw")
+            }
+            _ => name.clone(),
+        });
+        python_params.push(PythonParam {
+            name,
+            param_type: python_type(&param.ty.variant),
+        });
+    }
+
+    let wrap_result = match &return_type.variant {
+        TypeVariant::Struct(name) | TypeVariant::Enum(name) => Some(class_name(name)),
+        TypeVariant::String | TypeVariant::Data => {
+            Some(format!("{}_take", helper_prefix(&return_type.variant)))
+        }
+        _ => None,
+    };
+
+    // E.g. `TWPublicKeyIsValid` -> `is_valid`.
+    let name = c_ffi_name
+        .strip_prefix(&strct.name)
+        .unwrap_or(c_ffi_name)
+        .to_snake_case();
+
+    PythonMethod {
+        name,
+        c_ffi_name: c_ffi_name.to_string(),
+        is_static,
+        is_property,
+        params: python_params,
+        argtypes,
+        restype: ctype(&return_type.variant),
+        args,
+        temporaries,
+        wrap_result,
+    }
+}
+
+// The prefix of the generated helpers converting strings or data, e.g.
+// `_string_create`.
+fn helper_prefix(ty: &TypeVariant) -> &'static str {
+    match ty {
+        TypeVariant::String => "_string",
+        _ => "_data",
+    }
+}
+
+impl<'a> CodegenTarget for PythonTarget<'a> {
+    fn map_type(&self, ty: &TypeVariant) -> String {
+        python_type(ty)
+    }
+
+    fn render_struct(&self, strct: &StructInfo) -> Result<Option<(String, String)>> {
+        let methods = self.methods(strct);
+
+        // Avoid rendering empty classes.
+        if methods.is_empty() {
+            return Ok(None);
+        }
+
+        let class = PythonClass {
+            name: class_name(&strct.name),
+            uses_strings: self.uses_type(strct, &TypeVariant::String),
+            uses_data: self.uses_type(strct, &TypeVariant::Data),
+            methods,
+            deinit: self.deinit(strct),
+            imports: self.imports(strct),
+        };
+
+        let out = self.engine.render("class", &class)?;
+        Ok(Some((class.name, out)))
+    }
+
+    fn render_enum(&self, enm: &EnumInfo) -> Result<Option<(String, String)>> {
+        let enm = PythonEnum {
+            name: class_name(&enm.name),
+            variants: enm
+                .variants
+                .iter()
+                .map(|variant| PythonEnumVariant {
+                    name: variant.name.to_uppercase(),
+                    value: variant.value.clone(),
+                })
+                .collect(),
+        };
+
+        let out = self.engine.render("enum", &enm)?;
+        Ok(Some((enm.name, out)))
+    }
+}
+
+/// Renders the Python wrappers of all structs and enums of the manifest file.
+pub fn render_to_strings(file_info: FileInfo) -> Result<GeneratedStrings> {
+    let target = PythonTarget::new(file_info.clone())?;
+    render_file_info(&target, &file_info)
+}
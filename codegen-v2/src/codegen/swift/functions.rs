@@ -26,6 +26,12 @@ pub(super) fn process_methods(
             continue;
         }
 
+        // Swift cannot call variadic C functions directly, skip those.
+        if func.is_variadic {
+            println!("Skipping variadic function: {}", func.name);
+            continue;
+        }
+
         let mut ops = vec![];
 
         // Initalize the 'self' type, which is then passed on to the underlying
@@ -142,8 +148,10 @@ pub(super) fn process_methods(
 
         swift_funcs.push(SwiftFunction {
             name: pretty_name,
+            c_ffi_name: func.name,
             is_public: func.is_public,
             is_static: func.is_static,
+            availability: func.availability,
             operations: ops,
             params,
             return_type,
@@ -79,6 +79,7 @@ pub(super) fn process_inits(
 
         swift_inits.push(SwiftInit {
             name: pretty_name,
+            c_ffi_name: init.name,
             is_nullable: init.is_nullable,
             is_public: init.is_public,
             params,
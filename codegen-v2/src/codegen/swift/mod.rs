@@ -19,8 +19,8 @@ mod render;
 
 // Re-exports
 pub use self::render::{
-    generate_swift_types, render_to_strings, GeneratedSwiftTypes, GeneratedSwiftTypesStrings,
-    RenderIntput,
+    generate_swift_types, render_to_strings, FileNaming, GeneratedSwiftDiff, GeneratedSwiftIndex,
+    GeneratedSwiftTypes, GeneratedSwiftTypesStrings, RenderIntput, SwiftTarget,
 };
 
 /// Represents a Swift struct or class.
@@ -46,6 +46,7 @@ pub struct SwiftEnum {
     add_description: bool,
     superclasses: Vec<String>,
     variants: Vec<SwiftEnumVariant>,
+    aliases: Vec<SwiftEnumAlias>,
 }
 
 /// Represents a Swift enum variant.
@@ -56,6 +57,15 @@ pub struct SwiftEnumVariant {
     as_string: Option<String>,
 }
 
+/// Represents a variant that aliases another variant, i.e. has the same value.
+/// Swift forbids duplicate raw values, so aliases are rendered as static
+/// constants referencing the original variant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwiftEnumAlias {
+    name: String,
+    target: String,
+}
+
 /// Represents associated methods and properties of an enum. Based on the first
 /// codegen, those extensions are placed in a separate file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,8 +91,12 @@ impl Display for SwiftType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwiftFunction {
     pub name: String,
+    // The original name of the underlying C FFI symbol.
+    pub c_ffi_name: String,
     pub is_public: bool,
     pub is_static: bool,
+    // Rendered as `@available(...)`, if set.
+    pub availability: Option<String>,
     pub params: Vec<SwiftParam>,
     pub operations: Vec<SwiftOperation>,
     #[serde(rename = "return")]
@@ -94,7 +108,10 @@ pub struct SwiftFunction {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SwiftProperty {
     pub name: String,
+    // The original name of the underlying C FFI symbol.
+    pub c_ffi_name: String,
     pub is_public: bool,
+    pub is_static: bool,
     pub operations: Vec<SwiftOperation>,
     #[serde(rename = "return")]
     pub return_type: SwiftReturn,
@@ -171,6 +188,8 @@ pub struct SwiftReturn {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwiftInit {
     pub name: String,
+    // The original name of the underlying C FFI symbol.
+    pub c_ffi_name: String,
     pub is_nullable: bool,
     pub is_public: bool,
     pub params: Vec<SwiftParam>,
@@ -221,7 +240,7 @@ impl From<TypeVariant> for SwiftType {
         let res = match value {
             TypeVariant::Void => "Void".to_string(),
             TypeVariant::Bool => "Bool".to_string(),
-            TypeVariant::Char => "Character".to_string(),
+            TypeVariant::Char => "CChar".to_string(),
             TypeVariant::ShortInt => "Int16".to_string(),
             TypeVariant::Int => "Int32".to_string(),
             TypeVariant::UnsignedInt => "UInt32".to_string(),
@@ -29,26 +29,33 @@ pub(super) fn process_properties(
         let mut ops = vec![];
 
         // Initalize the 'self' type, which is then passed on to the underlying
-        // C FFI function.
-        ops.push(match object {
-            // E.g. `let obj = self.rawValue`
-            ObjectVariant::Struct(_) => SwiftOperation::Call {
-                var_name: "obj".to_string(),
-                call: "self.rawValue".to_string(),
-                defer: None,
-            },
-            // E.g. `let obj = TWSomeEnum(rawValue: self.rawValue")`
-            ObjectVariant::Enum(name) => SwiftOperation::Call {
-                var_name: "obj".to_string(),
-                call: format!("{}(rawValue: self.rawValue)", name),
-                defer: None,
-            },
-        });
+        // C FFI function, assuming the property is not static.
+        if !prop.is_static {
+            ops.push(match object {
+                // E.g. `let obj = self.rawValue`
+                ObjectVariant::Struct(_) => SwiftOperation::Call {
+                    var_name: "obj".to_string(),
+                    call: "self.rawValue".to_string(),
+                    defer: None,
+                },
+                // E.g. `let obj = TWSomeEnum(rawValue: self.rawValue")`
+                ObjectVariant::Enum(name) => SwiftOperation::Call {
+                    var_name: "obj".to_string(),
+                    call: format!("{}(rawValue: self.rawValue)", name),
+                    defer: None,
+                },
+            });
+        }
 
-        // Call the underlying C FFI function, passing on the `obj` instance.
+        // Call the underlying C FFI function, passing on the `obj` instance
+        // (if not static).
         //
         // E.g: `let result = TWSomeFunc(obj)`.
-        let (var_name, call) = ("result".to_string(), format!("{}(obj)", prop.name));
+        let param_name = if prop.is_static { "" } else { "obj" };
+        let (var_name, call) = (
+            "result".to_string(),
+            format!("{}({})", prop.name, param_name),
+        );
         if prop.return_type.is_nullable {
             ops.push(SwiftOperation::GuardedCall { var_name, call });
         } else {
@@ -78,7 +85,9 @@ pub(super) fn process_properties(
 
         swift_props.push(SwiftProperty {
             name: pretty_name,
+            c_ffi_name: prop.name,
             is_public: prop.is_public,
+            is_static: prop.is_static,
             operations: ops,
             return_type,
             comments: vec![],
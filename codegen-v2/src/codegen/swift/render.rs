@@ -3,6 +3,9 @@
 // Copyright © 2017 Trust Wallet.
 
 use super::{inits::process_deinits, *};
+use crate::codegen::target::{render_file_info, CodegenTarget};
+use crate::manifest::{EnumInfo, StructInfo};
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct RenderIntput<'a> {
@@ -14,6 +17,10 @@ pub struct RenderIntput<'a> {
     pub partial_init_template: &'a str,
     pub partial_func_tempalte: &'a str,
     pub partial_prop_tempalte: &'a str,
+    /// Whether unmatched variables in the templates result in an error. This
+    /// should usually be enabled; templates that reference optional fields
+    /// (such as deprecation messages) can opt out.
+    pub strict: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -24,6 +31,281 @@ pub struct GeneratedSwiftTypesStrings {
     pub protos: Vec<(String, String)>,
 }
 
+/// How the generated bindings are split into files, see
+/// [`GeneratedSwiftTypesStrings::files`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileNaming {
+    /// One file per type, named after the type: structs in `<Name>.swift`,
+    /// enums in `Enums/<Name>.swift`, enum extensions in
+    /// `<Name>+Extension.swift` and protobuf declarations in
+    /// `Protobuf/<Name>+Proto.swift`.
+    #[default]
+    PerType,
+    /// A single `<Name>.swift` file per manifest file, named after the
+    /// `FileInfo`, containing all of its generated types.
+    PerFileInfo,
+}
+
+/// An index of the generated type names, grouped the same way as
+/// [`GeneratedSwiftTypesStrings`]. Meant for build systems that need to know
+/// which files were generated.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedSwiftIndex {
+    pub structs: Vec<String>,
+    pub enums: Vec<String>,
+    pub extensions: Vec<String>,
+    pub protos: Vec<String>,
+}
+
+impl GeneratedSwiftTypesStrings {
+    /// Creates an index listing the names of all generated types.
+    pub fn index(&self) -> GeneratedSwiftIndex {
+        let names = |rendered: &[(String, String)]| {
+            rendered
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        GeneratedSwiftIndex {
+            structs: names(&self.structs),
+            enums: names(&self.enums),
+            extensions: names(&self.extensions),
+            protos: names(&self.protos),
+        }
+    }
+
+    /// Returns the generated files as `(path, content)` pairs, named
+    /// according to the given strategy. The paths are relative to the output
+    /// directory. `file_info_name` is the name of the rendered `FileInfo`.
+    pub fn files(&self, naming: FileNaming, file_info_name: &str) -> Vec<(String, String)> {
+        let per_type = self
+            .structs
+            .iter()
+            .map(|(name, content)| (format!("{name}.swift"), content))
+            .chain(
+                self.enums
+                    .iter()
+                    .map(|(name, content)| (format!("Enums/{name}.swift"), content)),
+            )
+            .chain(
+                self.extensions
+                    .iter()
+                    .map(|(name, content)| (format!("{name}+Extension.swift"), content)),
+            )
+            .chain(
+                self.protos
+                    .iter()
+                    .map(|(name, content)| (format!("Protobuf/{name}+Proto.swift"), content)),
+            );
+
+        match naming {
+            FileNaming::PerType => per_type
+                .map(|(path, content)| (path, content.clone()))
+                .collect(),
+            FileNaming::PerFileInfo => {
+                let contents: Vec<&str> = per_type.map(|(_, content)| content.as_str()).collect();
+                if contents.is_empty() {
+                    return vec![];
+                }
+
+                let file_name = format!("{}.swift", pretty_name(file_info_name.to_string()));
+                vec![(file_name, merge_swift_files(&contents))]
+            }
+        }
+    }
+
+    /// Reads previously generated bindings from the given directory, which
+    /// is expected to have the layout written by the `swift` command: structs
+    /// and extensions (`+Extension`) in the directory itself, enums in
+    /// `Enums/` and protobuf declarations (`+Proto`) in `Protobuf/`.
+    pub fn read_from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut generated = GeneratedSwiftTypesStrings::default();
+
+        for (name, content) in read_swift_files(dir)? {
+            match name.strip_suffix("+Extension") {
+                Some(name) => generated.extensions.push((name.to_string(), content)),
+                None => generated.structs.push((name, content)),
+            }
+        }
+
+        generated.enums = read_swift_files(&dir.join("Enums"))?;
+
+        for (name, content) in read_swift_files(&dir.join("Protobuf"))? {
+            if let Some(name) = name.strip_suffix("+Proto") {
+                generated.protos.push((name.to_string(), content));
+            }
+        }
+
+        Ok(generated)
+    }
+
+    /// Compares the generated bindings against `previous` ones, e.g. as read
+    /// via [`GeneratedSwiftTypesStrings::read_from_dir`].
+    pub fn diff(&self, previous: &GeneratedSwiftTypesStrings) -> GeneratedSwiftDiff {
+        let mut diff = GeneratedSwiftDiff::default();
+
+        diff_rendered(&self.structs, &previous.structs, &mut diff, |index| {
+            &mut index.structs
+        });
+        diff_rendered(&self.enums, &previous.enums, &mut diff, |index| {
+            &mut index.enums
+        });
+        diff_rendered(&self.extensions, &previous.extensions, &mut diff, |index| {
+            &mut index.extensions
+        });
+        diff_rendered(&self.protos, &previous.protos, &mut diff, |index| {
+            &mut index.protos
+        });
+
+        diff
+    }
+}
+
+impl GeneratedSwiftIndex {
+    pub fn is_empty(&self) -> bool {
+        self.structs.is_empty()
+            && self.enums.is_empty()
+            && self.extensions.is_empty()
+            && self.protos.is_empty()
+    }
+}
+
+/// The type names that were added, removed or modified compared to
+/// previously generated bindings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedSwiftDiff {
+    pub added: GeneratedSwiftIndex,
+    pub removed: GeneratedSwiftIndex,
+    pub modified: GeneratedSwiftIndex,
+}
+
+impl GeneratedSwiftDiff {
+    /// Whether the bindings are unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+// Compares the rendered types of a single kind, `select` returns the
+// corresponding list of the index.
+fn diff_rendered(
+    current: &[(String, String)],
+    previous: &[(String, String)],
+    diff: &mut GeneratedSwiftDiff,
+    select: fn(&mut GeneratedSwiftIndex) -> &mut Vec<String>,
+) {
+    for (name, content) in current {
+        match previous.iter().find(|(prev_name, _)| prev_name == name) {
+            None => select(&mut diff.added).push(name.clone()),
+            Some((_, prev_content)) if prev_content != content => {
+                select(&mut diff.modified).push(name.clone())
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (name, _) in previous {
+        if !current.iter().any(|(cur_name, _)| cur_name == name) {
+            select(&mut diff.removed).push(name.clone());
+        }
+    }
+}
+
+// Compares the raw values of two enum variants, e.g. `1` equals `0x1` and
+// `01`. Falls back to comparing the strings if either value is not an integer.
+fn enum_values_equal(a: &str, b: &str) -> bool {
+    match (parse_enum_value(a), parse_enum_value(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// Parses a decimal or hexadecimal (`0x`) integer.
+fn parse_enum_value(value: &str) -> Option<i128> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => value.parse::<i128>().ok()?,
+    };
+
+    Some(if negative { -parsed } else { parsed })
+}
+
+// Merges the rendered files into a single file. The header comment is taken
+// from the first file and the imports of all files are deduplicated, followed
+// by the bodies of the files.
+fn merge_swift_files(contents: &[&str]) -> String {
+    let (mut header, mut imports, mut bodies) = (vec![], vec![], vec![]);
+
+    for (index, content) in contents.iter().enumerate() {
+        let mut lines = content.lines().peekable();
+
+        // The license and "GENERATED FILE" banner.
+        while let Some(line) = lines.next_if(|line| line.starts_with("//")) {
+            if index == 0 {
+                header.push(line);
+            }
+        }
+
+        while let Some(line) =
+            lines.next_if(|line| line.trim().is_empty() || line.starts_with("import "))
+        {
+            if line.starts_with("import ") && !imports.contains(&line) {
+                imports.push(line);
+            }
+        }
+
+        bodies.push(lines.collect::<Vec<_>>().join("\n"));
+    }
+
+    let mut merged = String::new();
+    for block in [header.join("\n"), imports.join("\n")] {
+        if !block.is_empty() {
+            merged.push_str(&block);
+            merged.push_str("\n\n");
+        }
+    }
+    merged.push_str(&bodies.join("\n\n"));
+    merged.push('\n');
+
+    merged
+}
+
+// Reads the `.swift` files of the given directory (non-recursive), sorted by
+// name. A missing directory is treated as empty.
+fn read_swift_files(dir: &Path) -> Result<Vec<(String, String)>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("swift") {
+            continue;
+        }
+
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        files.push((name.to_string(), std::fs::read_to_string(&path)?));
+    }
+
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(files)
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct GeneratedSwiftTypes {
     pub structs: Vec<SwiftStruct>,
@@ -44,76 +326,105 @@ pub fn pretty_name(name: String) -> String {
     name.replace("_", "").replace("TW", "").replace("Proto", "")
 }
 
-pub fn render_to_strings<'a>(input: RenderIntput<'a>) -> Result<GeneratedSwiftTypesStrings> {
-    // The current year for the copyright header in the generated bindings.
-    let current_year = crate::current_year();
-    // Convert the name into an appropriate format.
-    let pretty_file_name = pretty_name(input.file_info.name.clone());
+/// The Swift [`CodegenTarget`]. The manifest is converted into the Swift
+/// representation upfront, since methods and properties are assigned to the
+/// types in the order of declaration.
+pub struct SwiftTarget<'a> {
+    engine: Handlebars<'a>,
+    current_year: u64,
+    types: GeneratedSwiftTypes,
+}
+
+impl<'a> SwiftTarget<'a> {
+    pub fn new(input: RenderIntput<'a>) -> Result<Self> {
+        let mut engine = Handlebars::new();
+        // Unmatched variables should result in an error, unless disabled.
+        engine.set_strict_mode(input.strict);
+
+        engine.register_partial("struct", input.struct_template)?;
+        engine.register_partial("enum", input.enum_template)?;
+        engine.register_partial("extension", input.extension_template)?;
+        engine.register_partial("proto", input.proto_template)?;
+        engine.register_partial("partial_init", input.partial_init_template)?;
+        engine.register_partial("partial_func", input.partial_func_tempalte)?;
+        engine.register_partial("partial_prop", input.partial_prop_tempalte)?;
+
+        Ok(SwiftTarget {
+            engine,
+            // The current year for the copyright header in the generated bindings.
+            current_year: crate::current_year(),
+            types: generate_swift_types(input.file_info)?,
+        })
+    }
 
-    let mut engine = Handlebars::new();
-    // Unmatched variables should result in an error.
-    engine.set_strict_mode(true);
-
-    engine.register_partial("struct", input.struct_template)?;
-    engine.register_partial("enum", input.enum_template)?;
-    engine.register_partial("extension", input.extension_template)?;
-    engine.register_partial("proto", input.proto_template)?;
-    engine.register_partial("partial_init", input.partial_init_template)?;
-    engine.register_partial("partial_func", input.partial_func_tempalte)?;
-    engine.register_partial("partial_prop", input.partial_prop_tempalte)?;
-
-    let rendered = generate_swift_types(input.file_info)?;
-    let mut out_str = GeneratedSwiftTypesStrings::default();
-
-    //  Render structs.
-    for strct in rendered.structs {
-        let out = engine.render(
-            "struct",
+    fn render<T: serde::Serialize>(&self, template: &str, data: &T) -> Result<String> {
+        let out = self.engine.render(
+            template,
             &WithYear {
-                current_year,
-                data: &strct,
+                current_year: self.current_year,
+                data,
             },
         )?;
 
-        out_str.structs.push((strct.name, out));
+        Ok(out)
     }
+}
 
-    //  Render enums.
-    for enm in rendered.enums {
-        let out = engine.render(
-            "enum",
-            &WithYear {
-                current_year,
-                data: &enm,
-            },
-        )?;
+impl<'a> CodegenTarget for SwiftTarget<'a> {
+    fn map_type(&self, ty: &TypeVariant) -> String {
+        SwiftType::from(ty.clone()).0
+    }
 
-        out_str.enums.push((enm.name, out));
+    fn render_struct(&self, strct: &StructInfo) -> Result<Option<(String, String)>> {
+        let name = pretty_name(strct.name.clone());
+
+        // Empty structs are not rendered.
+        let Some(strct) = self.types.structs.iter().find(|s| s.name == name) else {
+            return Ok(None);
+        };
+
+        Ok(Some((name, self.render("struct", strct)?)))
     }
 
-    //  Render extensions.
-    for ext in rendered.extensions {
-        let out = engine.render(
-            "extension",
-            &WithYear {
-                current_year,
-                data: &ext,
-            },
-        )?;
+    fn render_enum(&self, enm: &EnumInfo) -> Result<Option<(String, String)>> {
+        let name = pretty_name(enm.name.clone());
+
+        let Some(enm) = self.types.enums.iter().find(|e| e.name == name) else {
+            return Ok(None);
+        };
 
-        out_str.extensions.push((ext.name, out));
+        Ok(Some((name, self.render("enum", enm)?)))
+    }
+}
+
+pub fn render_to_strings<'a>(input: RenderIntput<'a>) -> Result<GeneratedSwiftTypesStrings> {
+    // Convert the name into an appropriate format.
+    let pretty_file_name = pretty_name(input.file_info.name.clone());
+
+    let file_info = input.file_info.clone();
+    let target = SwiftTarget::new(input)?;
+
+    //  Render structs and enums.
+    let rendered = render_file_info(&target, &file_info)?;
+    let mut out_str = GeneratedSwiftTypesStrings {
+        structs: rendered.structs,
+        enums: rendered.enums,
+        ..Default::default()
+    };
+
+    //  Render extensions.
+    for ext in &target.types.extensions {
+        let out = target.render("extension", ext)?;
+        out_str.extensions.push((ext.name.clone(), out));
     }
 
     //  Render protos.
-    if !rendered.protos.is_empty() {
-        let out = engine.render(
+    if !target.types.protos.is_empty() {
+        let out = target.render(
             "proto",
-            &WithYear {
-                current_year,
-                data: &json!({
-                    "protos": &rendered.protos
-                }),
-            },
+            &json!({
+                "protos": &target.types.protos
+            }),
         )?;
 
         out_str.protos.push((pretty_file_name, out));
@@ -199,22 +510,32 @@ pub fn generate_swift_types(mut info: FileInfo) -> Result<GeneratedSwiftTypes> {
 
         let mut add_class = false;
 
-        // Convert to Swift enum variants
-        let variants = enm
-            .variants
-            .into_iter()
-            .map(|info| {
-                if info.as_string.is_some() {
-                    add_class = true;
-                }
-
-                SwiftEnumVariant {
+        // Convert to Swift enum variants. Variants with an already seen value
+        // alias the first variant with that value.
+        let mut variants: Vec<SwiftEnumVariant> = vec![];
+        let mut aliases = vec![];
+        for info in enm.variants {
+            if let Some(target) = variants
+                .iter()
+                .find(|v| enum_values_equal(&v.value, &info.value))
+            {
+                aliases.push(SwiftEnumAlias {
                     name: info.name,
-                    value: info.value,
-                    as_string: info.as_string,
-                }
-            })
-            .collect();
+                    target: target.name.clone(),
+                });
+                continue;
+            }
+
+            if info.as_string.is_some() {
+                add_class = true;
+            }
+
+            variants.push(SwiftEnumVariant {
+                name: info.name,
+                value: info.value,
+                as_string: info.as_string,
+            });
+        }
 
         if add_class {
             superclasses.push("CustomStringConvertible".to_string());
@@ -226,6 +547,7 @@ pub fn generate_swift_types(mut info: FileInfo) -> Result<GeneratedSwiftTypes> {
             add_description: add_class,
             superclasses,
             variants,
+            aliases,
         });
 
         // Avoid rendering empty extension for enums.
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+
+use crate::manifest::{EnumInfo, FileInfo, StructInfo, TypeVariant};
+use crate::Result;
+
+/// A language target of the bindings generator. The target is driven by
+/// [`render_file_info`], which walks the manifest and calls the target for
+/// each declared type.
+pub trait CodegenTarget {
+    /// Maps the manifest type to the type of the target language.
+    fn map_type(&self, ty: &TypeVariant) -> String;
+
+    /// Renders the given struct, returning the type name and the rendered
+    /// file. Returns `None` if the struct is not rendered (e.g. empty).
+    fn render_struct(&self, strct: &StructInfo) -> Result<Option<(String, String)>>;
+
+    /// Renders the given enum, returning the type name and the rendered file.
+    /// Returns `None` if the enum is not rendered.
+    fn render_enum(&self, enm: &EnumInfo) -> Result<Option<(String, String)>>;
+}
+
+/// The rendered structs and enums of a single manifest file.
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedStrings {
+    pub structs: Vec<(String, String)>,
+    pub enums: Vec<(String, String)>,
+}
+
+/// Renders all structs and enums of the manifest file with the given target.
+pub fn render_file_info<T: CodegenTarget>(target: &T, info: &FileInfo) -> Result<GeneratedStrings> {
+    let mut out = GeneratedStrings::default();
+
+    for strct in &info.structs {
+        if let Some(rendered) = target.render_struct(strct)? {
+            out.structs.push(rendered);
+        }
+    }
+
+    for enm in &info.enums {
+        if let Some(rendered) = target.render_enum(enm)? {
+            out.enums.push(rendered);
+        }
+    }
+
+    Ok(out)
+}
@@ -28,6 +28,7 @@ pub enum Error {
     RenderError(RenderError),
     TemplateError(TemplateError),
     BadFormat(String),
+    BadType(String),
     RegistryError(String),
     TomlFormat(String),
     InvalidCommand,
@@ -2,13 +2,14 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use libparser::codegen::swift::RenderIntput;
+use libparser::codegen::swift::{FileNaming, RenderIntput};
 use libparser::codegen::{cpp, proto, rust};
 use libparser::coin_id::CoinId;
-use libparser::manifest::parse_dir;
+use libparser::manifest::{parse_dir, validate_type_references};
 use libparser::registry::read_coin_from_registry;
 use libparser::{Error, Result};
 use std::fs::read_to_string;
+use std::path::Path;
 
 fn main() -> Result<()> {
     let args: Vec<String> = std::env::args().collect();
@@ -95,6 +96,7 @@ fn generate_swift_bindings() -> Result<()> {
 
     // Read the manifest dir, generate bindings for each entry.
     let file_infos = parse_dir("manifest/")?;
+    validate_type_references(&file_infos)?;
 
     for file_info in file_infos {
         let input = RenderIntput {
@@ -106,40 +108,20 @@ fn generate_swift_bindings() -> Result<()> {
             partial_init_template: &part_init_t,
             partial_func_tempalte: &part_func_t,
             partial_prop_tempalte: &part_prop_t,
+            strict: true,
         };
 
+        let file_info_name = input.file_info.name.clone();
         let rendered = libparser::codegen::swift::render_to_strings(input)?;
 
-        // Enum declarations go into their own subfolder.
-        if !rendered.enums.is_empty() {
-            std::fs::create_dir_all(format!("{OUT_DIR}/Enums"))?;
-        }
-
-        // Protobuf declarations go into their own subfolder.
-        if !rendered.protos.is_empty() {
-            std::fs::create_dir_all(format!("{OUT_DIR}/Protobuf"))?;
-        }
-
-        for (name, rendered) in rendered.structs {
-            let file_path = format!("{OUT_DIR}/{name}.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
-        }
-
-        for (name, rendered) in rendered.enums {
-            let file_path = format!("{OUT_DIR}/Enums/{name}.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
-        }
-
-        // Enum extensions.
-        for (name, rendered) in rendered.extensions {
-            let file_path = format!("{OUT_DIR}/{name}+Extension.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
-        }
+        // Enum and protobuf declarations go into their own subfolders.
+        for (path, content) in rendered.files(FileNaming::PerType, &file_info_name) {
+            let file_path = Path::new(OUT_DIR).join(path);
+            if let Some(parent) = file_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
 
-        // Protobuf messages.
-        for (name, rendered) in rendered.protos {
-            let file_path = format!("{OUT_DIR}/Protobuf/{name}+Proto.swift");
-            std::fs::write(&file_path, rendered.as_bytes())?;
+            std::fs::write(&file_path, content.as_bytes())?;
         }
     }
 
@@ -2,40 +2,120 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use super::Result;
+use super::{Error, Result};
+use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
 
+/// Parses all manifest files of the given directory. The files, as well as
+/// their structs, enums and functions, are sorted by name, which makes the
+/// generated output reproducible.
 pub fn parse_dir<P: AsRef<Path>>(path: P) -> Result<Vec<FileInfo>> {
-    // Get a list of all files in the directory
-    let entries = fs::read_dir(path)?;
+    let mut file_infos = iter_file_infos(path)?.collect::<Result<Vec<_>>>()?;
 
-    let mut file_infos = vec![];
-    for entry in entries {
-        let entry = entry?;
-        let file_path = entry.path();
+    for file_info in &mut file_infos {
+        file_info.sort_by_name();
+    }
+    file_infos.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(file_infos)
+}
+
+/// Lazily parses the manifest files of the given directory, yielding one
+/// `FileInfo` at a time. Errors of individual files are yielded rather than
+/// aborting the iteration, so callers can process (and write) each file
+/// incrementally.
+pub fn iter_file_infos<P: AsRef<Path>>(path: P) -> Result<impl Iterator<Item = Result<FileInfo>>> {
+    // Get a list of all files in the directory. `read_dir` yields the
+    // entries in an unspecified order, so sort them by path.
+    let mut entries: Vec<_> = fs::read_dir(path)?.collect();
+    entries.sort_by_key(|entry| entry.as_ref().ok().map(|entry| entry.path()));
+
+    let iter = entries.into_iter().filter_map(|entry| {
+        let file_path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => return Some(Err(err.into())),
+        };
 
         // Skip directories
         if file_path.is_dir() {
             println!("Found unexpected directory: {}", file_path.display());
-            continue;
+            return None;
         }
 
-        // Read the file into a string
-        let file_contents = fs::read_to_string(&file_path)?;
+        // Only YAML manifests are expected in the directory.
+        if file_path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+            return Some(Err(Error::BadFormat(format!(
+                "Expected a `.yaml` manifest file: {}",
+                file_path.display()
+            ))));
+        }
 
-        // Deserialize the JSON into a struct
-        let info = parse_str(&file_contents)?;
-        file_infos.push(info);
-    }
+        // Read the file into a string and deserialize it.
+        let info = fs::read_to_string(&file_path)
+            .map_err(Error::from)
+            .and_then(|file_contents| parse_str(&file_contents));
 
-    Ok(file_infos)
+        Some(info)
+    });
+
+    Ok(iter)
 }
 
 pub fn parse_str(str: &str) -> Result<FileInfo> {
     serde_yaml::from_str(str).map_err(|err| err.into())
 }
 
+/// Checks that every custom type (struct or enum) referenced by a field,
+/// parameter or return type is declared in one of the given files. Returns
+/// `Error::BadType` with the name of the first unknown type, which usually
+/// means that a type from a file that was not included is referenced.
+pub fn validate_type_references(file_infos: &[FileInfo]) -> Result<()> {
+    let declared: HashSet<&str> = file_infos
+        .iter()
+        .flat_map(|info| {
+            let structs = info.structs.iter().map(|s| s.name.as_str());
+            let enums = info.enums.iter().map(|e| e.name.as_str());
+            structs.chain(enums)
+        })
+        .collect();
+
+    for info in file_infos {
+        let fields = info
+            .structs
+            .iter()
+            .flat_map(|s| s.fields.iter().map(|(_, ty)| ty));
+        let init_params = info
+            .inits
+            .iter()
+            .flat_map(|i| i.params.iter().map(|p| &p.ty));
+        let func_types = info.functions.iter().flat_map(|f| {
+            f.params
+                .iter()
+                .map(|p| &p.ty)
+                .chain(std::iter::once(&f.return_type))
+        });
+        let prop_types = info.properties.iter().map(|p| &p.return_type);
+
+        for ty in fields
+            .chain(init_params)
+            .chain(func_types)
+            .chain(prop_types)
+        {
+            match &ty.variant {
+                TypeVariant::Struct(name) | TypeVariant::Enum(name)
+                    if !declared.contains(name.as_str()) =>
+                {
+                    return Err(Error::BadType(name.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TypeInfo {
     #[serde(flatten)]
@@ -72,7 +152,7 @@ pub enum TypeVariant {
     String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileInfo {
     pub name: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -91,17 +171,88 @@ pub struct FileInfo {
     pub protos: Vec<ProtoInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The version of the JSON manifest format written by [`FileInfo::write_to`]
+/// and [`write_manifest_json`].
+pub const MANIFEST_JSON_VERSION: u32 = 1;
+
+/// The versioned JSON envelope of persisted manifests.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestJson<T> {
+    version: u32,
+    data: T,
+}
+
+impl FileInfo {
+    /// Writes the file info as JSON to the given path, so that the parse and
+    /// the render step can be run separately.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_json(path, self)
+    }
+
+    /// Reads the file info from a JSON file written by [`FileInfo::write_to`].
+    pub fn read_from<P: AsRef<Path>>(path: P) -> Result<FileInfo> {
+        read_json(path)
+    }
+
+    /// Sorts the structs, enums and functions by name.
+    pub fn sort_by_name(&mut self) {
+        self.structs.sort_by(|a, b| a.name.cmp(&b.name));
+        self.enums.sort_by(|a, b| a.name.cmp(&b.name));
+        self.functions.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// Writes all file infos of a manifest as a single JSON file.
+pub fn write_manifest_json<P: AsRef<Path>>(path: P, file_infos: &[FileInfo]) -> Result<()> {
+    write_json(path, &file_infos)
+}
+
+/// Reads all file infos from a JSON file written by [`write_manifest_json`].
+pub fn read_manifest_json<P: AsRef<Path>>(path: P) -> Result<Vec<FileInfo>> {
+    read_json(path)
+}
+
+fn write_json<P: AsRef<Path>, T: serde::Serialize>(path: P, data: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(&ManifestJson {
+        version: MANIFEST_JSON_VERSION,
+        data,
+    })
+    .map_err(|err| Error::BadFormat(err.to_string()))?;
+
+    fs::write(path, json)?;
+    Ok(())
+}
+
+fn read_json<P: AsRef<Path>, T: serde::de::DeserializeOwned>(path: P) -> Result<T> {
+    let contents = fs::read_to_string(path)?;
+
+    // Rejects unknown fields of the envelope and any mismatch of the manifest
+    // types.
+    let manifest: ManifestJson<T> =
+        serde_json::from_str(&contents).map_err(|err| Error::BadFormat(err.to_string()))?;
+
+    if manifest.version != MANIFEST_JSON_VERSION {
+        return Err(Error::BadFormat(format!(
+            "Unsupported manifest JSON version: {}",
+            manifest.version
+        )));
+    }
+
+    Ok(manifest.data)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImportInfo {
     // Expressed as directories plus the final file.
     // E.g. `to/some/file.h` ~= ["to", "some", "file.h"]
     pub path: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProtoInfo(pub String);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumInfo {
     pub name: String,
     pub is_public: bool,
@@ -110,7 +261,7 @@ pub struct EnumInfo {
     pub variants: Vec<EnumVariantInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumVariantInfo {
     pub name: String,
     pub value: String,
@@ -118,7 +269,7 @@ pub struct EnumVariantInfo {
     pub as_string: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StructInfo {
     pub name: String,
     pub is_public: bool,
@@ -127,7 +278,7 @@ pub struct StructInfo {
     pub fields: Vec<(String, TypeInfo)>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InitInfo {
     pub name: String,
     pub is_public: bool,
@@ -138,16 +289,23 @@ pub struct InitInfo {
     pub comments: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeinitInfo {
     pub name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FunctionInfo {
     pub name: String,
     pub is_public: bool,
     pub is_static: bool,
+    /// Whether the C function takes variadic arguments (`...`).
+    #[serde(default)]
+    pub is_variadic: bool,
+    /// The arguments of the `TW_AVAILABLE(...)` marker of the C function,
+    /// e.g. `iOS 13.0, macOS 10.15, *`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub params: Vec<ParamInfo>,
     pub return_type: TypeInfo,
@@ -155,18 +313,204 @@ pub struct FunctionInfo {
     pub comments: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PropertyInfo {
     pub name: String,
     pub is_public: bool,
+    #[serde(default)]
+    pub is_static: bool,
     pub return_type: TypeInfo,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub comments: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ParamInfo {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: TypeInfo,
 }
+
+/// Returns a JSON Schema (draft 2020-12) describing the serialized manifest
+/// types, starting at `FileInfo`. This allows external tools to validate
+/// hand-written manifests.
+pub fn json_schema() -> serde_json::Value {
+    use serde_json::json;
+
+    // The names of the variants without a value are taken from the serde
+    // representation, so they can't get out of sync.
+    let unit_variants: Vec<serde_json::Value> = [
+        TypeVariant::Void,
+        TypeVariant::Bool,
+        TypeVariant::Char,
+        TypeVariant::ShortInt,
+        TypeVariant::Int,
+        TypeVariant::UnsignedInt,
+        TypeVariant::LongInt,
+        TypeVariant::Float,
+        TypeVariant::Double,
+        TypeVariant::SizeT,
+        TypeVariant::Int8T,
+        TypeVariant::Int16T,
+        TypeVariant::Int32T,
+        TypeVariant::Int64T,
+        TypeVariant::UInt8T,
+        TypeVariant::UInt16T,
+        TypeVariant::UInt32T,
+        TypeVariant::UInt64T,
+        TypeVariant::Data,
+        TypeVariant::String,
+    ]
+    .iter()
+    .map(|variant| {
+        serde_json::to_value(variant).expect("type variant is serializable")["variant"].clone()
+    })
+    .collect();
+
+    let strings = json!({ "type": "array", "items": { "type": "string" } });
+    let params = json!({ "type": "array", "items": { "$ref": "#/$defs/ParamInfo" } });
+    let list_of =
+        |def: &str| json!({ "type": "array", "items": { "$ref": format!("#/$defs/{def}") } });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "FileInfo",
+        "$ref": "#/$defs/FileInfo",
+        "$defs": {
+            "FileInfo": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "structs": list_of("StructInfo"),
+                    "inits": list_of("InitInfo"),
+                    "deinits": list_of("DeinitInfo"),
+                    "enums": list_of("EnumInfo"),
+                    "functions": list_of("FunctionInfo"),
+                    "properties": list_of("PropertyInfo"),
+                    "protos": strings,
+                },
+            },
+            // Internally tagged as `{ "variant": .., "value": .. }`.
+            "TypeVariant": {
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "required": ["variant"],
+                        "properties": { "variant": { "enum": unit_variants } },
+                    },
+                    {
+                        "type": "object",
+                        "required": ["variant", "value"],
+                        "properties": {
+                            "variant": { "enum": ["struct", "enum"] },
+                            "value": { "type": "string" },
+                        },
+                    },
+                ],
+            },
+            // The type variant is flattened into the type info.
+            "TypeInfo": {
+                "allOf": [
+                    { "$ref": "#/$defs/TypeVariant" },
+                    {
+                        "type": "object",
+                        "required": ["is_constant", "is_nullable", "is_pointer"],
+                        "properties": {
+                            "is_constant": { "type": "boolean" },
+                            "is_nullable": { "type": "boolean" },
+                            "is_pointer": { "type": "boolean" },
+                        },
+                    },
+                ],
+            },
+            "StructInfo": {
+                "type": "object",
+                "required": ["name", "is_public", "is_class"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "is_public": { "type": "boolean" },
+                    "is_class": { "type": "boolean" },
+                    // A list of `[name, type]` pairs.
+                    "fields": {
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "prefixItems": [{ "type": "string" }, { "$ref": "#/$defs/TypeInfo" }],
+                            "minItems": 2,
+                            "maxItems": 2,
+                        },
+                    },
+                },
+            },
+            "EnumInfo": {
+                "type": "object",
+                "required": ["name", "is_public", "value_type"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "is_public": { "type": "boolean" },
+                    "value_type": { "$ref": "#/$defs/TypeVariant" },
+                    "variants": list_of("EnumVariantInfo"),
+                },
+            },
+            "EnumVariantInfo": {
+                "type": "object",
+                "required": ["name", "value"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "value": { "type": "string" },
+                    "as_string": { "type": "string" },
+                },
+            },
+            "InitInfo": {
+                "type": "object",
+                "required": ["name", "is_public", "is_nullable"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "is_public": { "type": "boolean" },
+                    "is_nullable": { "type": "boolean" },
+                    "params": params,
+                    "comments": strings,
+                },
+            },
+            "DeinitInfo": {
+                "type": "object",
+                "required": ["name"],
+                "properties": { "name": { "type": "string" } },
+            },
+            "FunctionInfo": {
+                "type": "object",
+                "required": ["name", "is_public", "is_static", "return_type"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "is_public": { "type": "boolean" },
+                    "is_static": { "type": "boolean" },
+                    "is_variadic": { "type": "boolean" },
+                    "availability": { "type": "string" },
+                    "params": params,
+                    "return_type": { "$ref": "#/$defs/TypeInfo" },
+                    "comments": strings,
+                },
+            },
+            "PropertyInfo": {
+                "type": "object",
+                "required": ["name", "is_public", "return_type"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "is_public": { "type": "boolean" },
+                    "is_static": { "type": "boolean" },
+                    "return_type": { "$ref": "#/$defs/TypeInfo" },
+                    "comments": strings,
+                },
+            },
+            "ParamInfo": {
+                "type": "object",
+                "required": ["name", "type"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "type": { "$ref": "#/$defs/TypeInfo" },
+                },
+            },
+        },
+    })
+}
@@ -2,8 +2,20 @@
 //
 // Copyright © 2017 Trust Wallet.
 
-use crate::codegen::swift::{render_to_strings, RenderIntput};
-use crate::manifest::parse_str;
+use crate::codegen::python;
+use crate::codegen::swift::{
+    generate_swift_types, render_to_strings, FileNaming, GeneratedSwiftTypesStrings, RenderIntput,
+    SwiftType,
+};
+use crate::codegen::target::{render_file_info, CodegenTarget};
+use crate::manifest::{
+    iter_file_infos, json_schema, parse_dir, parse_str, read_manifest_json,
+    validate_type_references, write_manifest_json, EnumInfo, FileInfo, StructInfo, TypeVariant,
+};
+use crate::Error;
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
 
 /// Convenience function.
 fn create_intput(yaml: &str) -> RenderIntput {
@@ -18,9 +30,19 @@ fn create_intput(yaml: &str) -> RenderIntput {
         partial_init_template: include_str!("../codegen/swift/templates/partial_init.hbs"),
         partial_func_tempalte: include_str!("../codegen/swift/templates/partial_func.hbs"),
         partial_prop_tempalte: include_str!("../codegen/swift/templates/partial_prop.hbs"),
+        strict: true,
     }
 }
 
+// Convenience function: creates a fresh, empty directory in the temporary
+// directory of the system.
+fn create_temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("codegen-v2-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
 // Convenience function: runs the codegen on the given `input` and compares it
 // with the `expected` value. Expects a single, rendered file as output.
 fn render_and_compare_struct(input: &str, expected: &str) {
@@ -128,3 +150,969 @@ fn non_associated() {
 
     render_and_compare_struct(INPUT, EXPECTED);
 }
+
+#[test]
+fn unknown_custom_type() {
+    const INPUT: &str = r#"
+name: Unknown
+structs:
+- name: MainStruct
+  is_public: true
+  is_class: false
+  fields:
+  - - field
+    - variant: struct
+      value: UndefinedStruct
+      is_constant: false
+      is_nullable: false
+      is_pointer: true
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    let err = validate_type_references(&[file_info]).unwrap_err();
+    assert!(matches!(err, Error::BadType(name) if name == "UndefinedStruct"));
+}
+
+#[test]
+fn known_custom_type() {
+    const INPUT: &str = include_str!("samples/class.input.yaml");
+
+    let file_info = parse_str(INPUT).unwrap();
+    validate_type_references(&[file_info]).unwrap();
+}
+
+#[test]
+fn iter_file_infos_yields_errors() {
+    let dir = create_temp_dir("iter");
+    fs::write(
+        dir.join("valid.yaml"),
+        include_str!("samples/class.input.yaml"),
+    )
+    .unwrap();
+    fs::write(dir.join("invalid.yaml"), "name: [").unwrap();
+
+    // The invalid file does not abort the iteration.
+    let results: Vec<_> = iter_file_infos(&dir).unwrap().collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.iter().filter(|res| res.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .any(|res| matches!(res, Err(Error::YamlError(_)))));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn iter_file_infos_non_yaml_file() {
+    let dir = create_temp_dir("non-yaml");
+    fs::write(dir.join("TWSomeHeader.h"), "// Not a manifest.").unwrap();
+
+    let results: Vec<_> = iter_file_infos(&dir).unwrap().collect();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(&results[0], Err(Error::BadFormat(msg)) if msg.contains("TWSomeHeader.h")));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parse_dir_sorted_by_name() {
+    let dir = create_temp_dir("sorted");
+    fs::write(
+        dir.join("b.yaml"),
+        "name: TWB\nstructs:\n- name: TWZeta\n  is_public: true\n  is_class: false\n- name: TWAlpha\n  is_public: true\n  is_class: false\n",
+    )
+    .unwrap();
+    fs::write(dir.join("c.yaml"), "name: TWC").unwrap();
+    fs::write(dir.join("a.yaml"), "name: TWA").unwrap();
+
+    let file_infos = parse_dir(&dir).unwrap();
+
+    let names: Vec<_> = file_infos.iter().map(|info| info.name.as_str()).collect();
+    assert_eq!(names, ["TWA", "TWB", "TWC"]);
+
+    // The declarations within a file are sorted, too.
+    let structs: Vec<_> = file_infos[1]
+        .structs
+        .iter()
+        .map(|strct| strct.name.as_str())
+        .collect();
+    assert_eq!(structs, ["TWAlpha", "TWZeta"]);
+
+    // The order is stable across runs.
+    for _ in 0..3 {
+        assert_eq!(parse_dir(&dir).unwrap(), file_infos);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn static_property() {
+    const INPUT: &str = include_str!("samples/static_property.input.yaml");
+    const EXPECTED: &str = include_str!("samples/static_property.output.swift");
+
+    let file_info = parse_str(INPUT).unwrap();
+    assert!(file_info.properties[0].is_static);
+    assert!(!file_info.properties[1].is_static);
+
+    render_and_compare_struct(INPUT, EXPECTED);
+}
+
+#[test]
+fn struct_init_signature() {
+    const INPUT: &str = r#"
+name: Init
+structs:
+- name: MainStruct
+  is_public: true
+  is_class: false
+inits:
+- name: MainStructCreateWithValue
+  is_public: true
+  is_nullable: false
+  params:
+  - name: name
+    type:
+      variant: string
+      is_constant: true
+      is_nullable: false
+      is_pointer: true
+  - name: value
+    type:
+      variant: int
+      is_constant: false
+      is_nullable: false
+      is_pointer: false
+"#;
+
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+    assert_eq!(rendered.structs.len(), 1);
+
+    let (name, output) = &rendered.structs[0];
+    assert_eq!(name, "MainStruct");
+    assert!(output.contains("public init(name: String, value: Int32) {"));
+}
+
+#[test]
+fn variadic_function() {
+    const INPUT: &str = r#"
+name: Variadic
+structs:
+- name: MainStruct
+  is_public: true
+  is_class: false
+functions:
+- name: MainStructFormat
+  is_public: true
+  is_static: true
+  is_variadic: true
+  params:
+  - name: format
+    type:
+      variant: string
+      is_constant: true
+      is_nullable: false
+      is_pointer: true
+  return_type:
+    variant: string
+    is_constant: true
+    is_nullable: false
+    is_pointer: true
+- name: MainStructFirstFunction
+  is_public: true
+  is_static: true
+  params:
+  - name: first_param
+    type:
+      variant: int
+      is_constant: false
+      is_nullable: false
+      is_pointer: false
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    let variadic = &file_info.functions[0];
+    assert!(variadic.is_variadic);
+    assert_eq!(variadic.params.len(), 1);
+    assert_eq!(variadic.params[0].name, "format");
+    assert!(!file_info.functions[1].is_variadic);
+
+    // Variadic functions are not rendered in Swift.
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(!output.contains("MainStructFormat"));
+    assert!(output.contains("MainStructFirstFunction"));
+}
+
+#[test]
+fn availability_marked_function() {
+    const INPUT: &str = r#"
+name: Availability
+structs:
+- name: MainStruct
+  is_public: true
+  is_class: false
+functions:
+- name: MainStructFirstFunction
+  is_public: true
+  is_static: true
+  availability: iOS 13.0, macOS 10.15, *
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+- name: MainStructSecondFunction
+  is_public: true
+  is_static: true
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    assert_eq!(
+        file_info.functions[0].availability.as_deref(),
+        Some("iOS 13.0, macOS 10.15, *")
+    );
+    assert!(file_info.functions[1].availability.is_none());
+
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+    let (_name, output) = &rendered.structs[0];
+    assert!(output.contains(
+        "    @available(iOS 13.0, macOS 10.15, *)\n    public static func firstFunction() -> Bool {"
+    ));
+    assert_eq!(output.matches("@available").count(), 1);
+}
+
+#[test]
+fn non_strict_optional_field() {
+    const INPUT: &str = include_str!("samples/struct.input.yaml");
+    // References a field that is not provided by the Swift struct data.
+    const TEMPLATE: &str = "struct {{name}} {}{{deprecation_message}}";
+
+    // Strict mode rejects the unknown field.
+    let mut input = create_intput(INPUT);
+    input.struct_template = TEMPLATE;
+    assert!(render_to_strings(input).is_err());
+
+    // Non-strict mode renders the missing field as empty.
+    let mut input = create_intput(INPUT);
+    input.struct_template = TEMPLATE;
+    input.strict = false;
+    let rendered = render_to_strings(input).unwrap();
+    assert_eq!(rendered.structs[0].1, "struct MainStruct {}");
+}
+
+#[test]
+fn swift_type_for_every_variant() {
+    let variants = [
+        (TypeVariant::Void, "Void"),
+        (TypeVariant::Bool, "Bool"),
+        (TypeVariant::Char, "CChar"),
+        (TypeVariant::ShortInt, "Int16"),
+        (TypeVariant::Int, "Int32"),
+        (TypeVariant::UnsignedInt, "UInt32"),
+        (TypeVariant::LongInt, "Int64"),
+        (TypeVariant::Float, "Float"),
+        (TypeVariant::Double, "Double"),
+        (TypeVariant::SizeT, "Int"),
+        (TypeVariant::Int8T, "Int8"),
+        (TypeVariant::Int16T, "Int16"),
+        (TypeVariant::Int32T, "Int32"),
+        (TypeVariant::Int64T, "Int64"),
+        (TypeVariant::UInt8T, "UInt8"),
+        (TypeVariant::UInt16T, "UInt16"),
+        (TypeVariant::UInt32T, "UInt32"),
+        (TypeVariant::UInt64T, "UInt64"),
+        (
+            TypeVariant::Struct("TWSomeStruct".to_string()),
+            "SomeStruct",
+        ),
+        (TypeVariant::Enum("TWSomeEnum".to_string()), "SomeEnum"),
+        (TypeVariant::Data, "Data"),
+        (TypeVariant::String, "String"),
+    ];
+
+    for (variant, expected) in variants {
+        let swift_type = SwiftType::from(variant).to_string();
+        assert_eq!(swift_type, expected);
+
+        // Must be a valid Swift identifier.
+        assert!(swift_type.starts_with(|c: char| c.is_ascii_alphabetic()));
+        assert!(swift_type.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
+
+#[test]
+fn original_c_ffi_names() {
+    const INPUT: &str = include_str!("samples/struct.input.yaml");
+
+    let file_info = parse_str(INPUT).unwrap();
+    let generated = generate_swift_types(file_info).unwrap();
+
+    // The template data contains both the pretty and the original names.
+    let data = serde_json::to_value(&generated.structs[0]).unwrap();
+
+    assert_eq!(data["inits"][0]["c_ffi_name"], "MainStructCreate");
+    assert_eq!(data["methods"][0]["name"], "firstFunction");
+    assert_eq!(data["methods"][0]["c_ffi_name"], "MainStructFirstFunction");
+    assert_eq!(data["properties"][0]["name"], "firstProperty");
+    assert_eq!(
+        data["properties"][0]["c_ffi_name"],
+        "MainStructFirstProperty"
+    );
+}
+
+#[test]
+fn generated_types_index() {
+    const INPUT: &str = r#"
+name: Index
+structs:
+- name: MainStruct
+  is_public: true
+  is_class: false
+enums:
+- name: MainEnum
+  is_public: true
+  value_type:
+    variant: u_int32_t
+  variants:
+  - name: one
+    value: 0
+functions:
+- name: MainStructIsValid
+  is_public: true
+  is_static: true
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+"#;
+
+    let input = create_intput(INPUT);
+    let rendered = render_to_strings(input).unwrap();
+    let index = rendered.index();
+
+    assert_eq!(index.structs, vec!["MainStruct"]);
+    assert_eq!(index.enums, vec!["MainEnum"]);
+    assert!(index.extensions.is_empty());
+    assert!(index.protos.is_empty());
+
+    let json = serde_json::to_value(&index).unwrap();
+    assert_eq!(json["structs"][0], "MainStruct");
+    assert_eq!(json["enums"][0], "MainEnum");
+}
+
+#[test]
+fn enum_with_negative_value() {
+    const INPUT: &str = r#"
+name: Status
+enums:
+- name: Status
+  is_public: true
+  value_type:
+    variant: int32_t
+  variants:
+  - name: err
+    value: -1
+  - name: ok
+    value: 0
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    let values: Vec<&str> = file_info.enums[0]
+        .variants
+        .iter()
+        .map(|variant| variant.value.as_str())
+        .collect();
+    assert_eq!(values, ["-1", "0"]);
+
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+
+    let (_name, output) = &rendered.enums[0];
+    assert!(output.contains("enum Status: Int32, CaseIterable {"));
+    assert!(output.contains("case `err` = -1"));
+    assert!(output.contains("case `ok` = 0"));
+}
+
+#[test]
+fn file_naming_strategies() {
+    const INPUT: &str = r#"
+name: TWMultiple
+structs:
+- name: TWFirstStruct
+  is_public: true
+  is_class: false
+- name: TWSecondStruct
+  is_public: true
+  is_class: false
+functions:
+- name: TWFirstStructFunction
+  is_public: true
+  is_static: true
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+- name: TWSecondStructFunction
+  is_public: true
+  is_static: true
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+"#;
+
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+
+    let files = rendered.files(FileNaming::PerType, "TWMultiple");
+    let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["FirstStruct.swift", "SecondStruct.swift"]);
+    assert_eq!(files[0].1, rendered.structs[0].1);
+
+    // All types of the manifest file end up in a single file.
+    let files = rendered.files(FileNaming::PerFileInfo, "TWMultiple");
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].0, "Multiple.swift");
+    assert!(files[0].1.contains("struct FirstStruct"));
+    assert!(files[0].1.contains("struct SecondStruct"));
+
+    // The header and imports appear only once.
+    assert!(files[0]
+        .1
+        .starts_with("// SPDX-License-Identifier: Apache-2.0\n"));
+    assert_eq!(files[0].1.matches("SPDX-License-Identifier").count(), 1);
+    assert_eq!(files[0].1.matches("GENERATED FILE").count(), 1);
+    assert_eq!(files[0].1.matches("import Foundation").count(), 1);
+
+    // Enums, extensions and protobuf declarations are placed in their
+    // subfolders.
+    let generated = GeneratedSwiftTypesStrings {
+        enums: vec![("Status".to_string(), String::new())],
+        extensions: vec![("Status".to_string(), String::new())],
+        protos: vec![("Bitcoin".to_string(), String::new())],
+        ..Default::default()
+    };
+    let files = generated.files(FileNaming::default(), "TWBitcoin");
+    let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Enums/Status.swift",
+            "Status+Extension.swift",
+            "Protobuf/Bitcoin+Proto.swift"
+        ]
+    );
+
+    // Nothing is generated.
+    let files = GeneratedSwiftTypesStrings::default().files(FileNaming::PerFileInfo, "TWEmpty");
+    assert!(files.is_empty());
+}
+
+#[test]
+fn python_bindings() {
+    const INPUT: &str = r#"
+name: TWPublicKey
+structs:
+- name: TWPublicKey
+  is_public: true
+  is_class: true
+enums:
+- name: TWPublicKeyType
+  is_public: true
+  value_type:
+    variant: u_int32_t
+  variants:
+  - name: secp256k1
+    value: 0
+  - name: ed25519
+    value: 1
+deinits:
+- name: TWPublicKeyDelete
+functions:
+- name: TWPublicKeyIsValid
+  is_public: true
+  is_static: true
+  params:
+  - name: data
+    type:
+      variant: data
+      is_constant: true
+      is_nullable: false
+      is_pointer: true
+  - name: type
+    type:
+      variant: enum
+      value: TWPublicKeyType
+      is_constant: false
+      is_nullable: false
+      is_pointer: false
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+- name: TWPublicKeyCreateWithHexString
+  is_public: true
+  is_static: true
+  params:
+  - name: hexString
+    type:
+      variant: string
+      is_constant: true
+      is_nullable: false
+      is_pointer: true
+  return_type:
+    variant: struct
+    value: TWPublicKey
+    is_constant: false
+    is_nullable: true
+    is_pointer: true
+properties:
+- name: TWPublicKeyKeyType
+  is_public: true
+  return_type:
+    variant: enum
+    value: TWPublicKeyType
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+- name: TWPublicKeyData
+  is_public: true
+  return_type:
+    variant: data
+    is_constant: false
+    is_nullable: false
+    is_pointer: true
+- name: TWPublicKeyDescription
+  is_public: true
+  return_type:
+    variant: string
+    is_constant: false
+    is_nullable: false
+    is_pointer: true
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    let rendered = python::render_to_strings(file_info).unwrap();
+
+    let (name, output) = &rendered.structs[0];
+    assert_eq!(name, "PublicKey");
+    assert!(output.contains("class PublicKey:"));
+
+    // The methods call into the shared library by the C symbol name.
+    assert!(output.contains(
+        "_lib.TWPublicKeyIsValid.argtypes = [ctypes.c_void_p, ctypes.c_int]\n\
+         _lib.TWPublicKeyIsValid.restype = ctypes.c_bool"
+    ));
+    assert!(output.contains(
+        "    @staticmethod\n    def is_valid(data: bytes, type: PublicKeyType):\n\
+         \x20       data_raw = _data_create(data)\n\
+         \x20       result = _lib.TWPublicKeyIsValid(data_raw, int(type))\n\
+         \x20       _data_delete(data_raw)\n\
+         \x20       return result"
+    ));
+    assert!(output.contains(
+        "    @property\n    def key_type(self):\n\
+         \x20       result = _lib.TWPublicKeyKeyType(self.raw_value)\n\
+         \x20       return PublicKeyType(result)"
+    ));
+
+    // Strings and data are converted from and to `str` and `bytes`, releasing
+    // the temporary and the returned objects.
+    assert!(output.contains(
+        "    def create_with_hex_string(hex_string: str):\n\
+         \x20       hex_string_raw = _string_create(hex_string)\n\
+         \x20       result = _lib.TWPublicKeyCreateWithHexString(hex_string_raw)\n\
+         \x20       _string_delete(hex_string_raw)\n\
+         \x20       return PublicKey(result)"
+    ));
+    assert!(output.contains(
+        "    def data(self):\n\
+         \x20       result = _lib.TWPublicKeyData(self.raw_value)\n\
+         \x20       return _data_take(result)"
+    ));
+    assert!(output.contains(
+        "    def description(self):\n\
+         \x20       result = _lib.TWPublicKeyDescription(self.raw_value)\n\
+         \x20       return _string_take(result)"
+    ));
+    assert!(output.contains("return _lib.TWStringCreateWithUTF8Bytes(value.encode(\"utf-8\"))"));
+    assert!(output.contains("return _lib.TWDataCreateWithBytes(value, len(value))"));
+    assert!(output.contains(
+        "    value = _lib.TWStringUTF8Bytes(raw_value).decode(\"utf-8\")\n\
+         \x20   _string_delete(raw_value)"
+    ));
+    assert!(output.contains(
+        "    value = ctypes.string_at(_lib.TWDataBytes(raw_value), _lib.TWDataSize(raw_value))\n\
+         \x20   _data_delete(raw_value)"
+    ));
+    assert!(output.contains("        _lib.TWStringDelete(raw_value)"));
+    assert!(output.contains("        _lib.TWDataDelete(raw_value)"));
+
+    // The underlying object is released by the deinit.
+    assert!(output.contains(
+        "_lib.TWPublicKeyDelete.argtypes = [ctypes.c_void_p]\n\
+         _lib.TWPublicKeyDelete.restype = None"
+    ));
+    assert!(output.contains(
+        "    def __del__(self):\n\
+         \x20       if self.raw_value is not None:\n\
+         \x20           _lib.TWPublicKeyDelete(self.raw_value)"
+    ));
+
+    // The referenced enum is imported from its own module.
+    assert_eq!(
+        output
+            .matches("from .PublicKeyType import PublicKeyType")
+            .count(),
+        1
+    );
+
+    let (name, output) = &rendered.enums[0];
+    assert_eq!(name, "PublicKeyType");
+    assert!(output.contains("class PublicKeyType(IntEnum):\n    SECP256K1 = 0\n    ED25519 = 1"));
+
+    let target = python::PythonTarget::new(parse_str("name: Empty").unwrap()).unwrap();
+    assert_eq!(target.map_type(&TypeVariant::UInt32T), "ctypes.c_uint32");
+    assert_eq!(target.map_type(&TypeVariant::String), "str");
+    assert_eq!(target.map_type(&TypeVariant::Data), "bytes");
+    assert_eq!(
+        target.map_type(&TypeVariant::Struct("TWPublicKey".to_string())),
+        "PublicKey"
+    );
+}
+
+#[test]
+fn python_bindings_shared_prefix() {
+    const INPUT: &str = r#"
+name: TWAny
+structs:
+- name: TWAny
+  is_public: true
+  is_class: true
+- name: TWAnySigner
+  is_public: true
+  is_class: true
+deinits:
+- name: TWAnySignerDelete
+- name: TWAnyDelete
+functions:
+- name: TWAnySignerSign
+  is_public: true
+  is_static: true
+  params: []
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+- name: TWAnyIsValid
+  is_public: true
+  is_static: true
+  params: []
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+- name: TWAnyhowIsValid
+  is_public: true
+  is_static: true
+  params: []
+  return_type:
+    variant: bool
+    is_constant: false
+    is_nullable: false
+    is_pointer: false
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    let rendered = python::render_to_strings(file_info).unwrap();
+    assert_eq!(rendered.structs.len(), 2);
+
+    // The functions and deinits of `TWAnySigner` do not land on `TWAny`, nor
+    // do symbols without an uppercase boundary after the struct name.
+    let (name, output) = &rendered.structs[0];
+    assert_eq!(name, "Any");
+    assert!(output.contains("def is_valid():"));
+    assert!(output.contains("_lib.TWAnyDelete(self.raw_value)"));
+    assert!(!output.contains("TWAnySigner"));
+    assert!(!output.contains("TWAnyhow"));
+
+    let (name, output) = &rendered.structs[1];
+    assert_eq!(name, "AnySigner");
+    assert!(output.contains("def sign():"));
+    assert!(output.contains("_lib.TWAnySignerDelete(self.raw_value)"));
+    assert!(!output.contains("TWAnyIsValid"));
+    assert!(!output.contains("TWAnyDelete"));
+}
+
+#[test]
+fn enum_with_aliased_variants() {
+    const INPUT: &str = r#"
+name: Status
+enums:
+- name: Status
+  is_public: true
+  value_type:
+    variant: int32_t
+  variants:
+  - name: ok
+    value: 0
+  - name: failed
+    value: 1
+  - name: success
+    value: 0
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    assert_eq!(file_info.enums[0].variants.len(), 3);
+
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+
+    // The alias is not rendered as a case, since Swift forbids duplicate raw
+    // values.
+    let (_name, output) = &rendered.enums[0];
+    assert!(output.contains("case `ok` = 0"));
+    assert!(output.contains("case `failed` = 1"));
+    assert_eq!(output.matches(" = 0").count(), 1);
+    assert!(output.contains("    public static let `success`: Self = .`ok`"));
+
+    // Values are compared as integers.
+    const NUMERIC_INPUT: &str = r#"
+name: Flags
+enums:
+- name: Flags
+  is_public: true
+  value_type:
+    variant: int32_t
+  variants:
+  - name: first
+    value: 1
+  - name: hex
+    value: 0x1
+  - name: padded
+    value: 01
+  - name: second
+    value: 2
+"#;
+
+    let rendered = render_to_strings(create_intput(NUMERIC_INPUT)).unwrap();
+    let (_name, output) = &rendered.enums[0];
+    assert_eq!(output.matches("case ").count(), 2);
+    assert!(output.contains("    public static let `hex`: Self = .`first`"));
+    assert!(output.contains("    public static let `padded`: Self = .`first`"));
+}
+
+#[test]
+fn generated_types_diff() {
+    let rendered = |items: &[(&str, &str)]| {
+        items
+            .iter()
+            .map(|(name, content)| (name.to_string(), content.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    let previous = GeneratedSwiftTypesStrings {
+        structs: rendered(&[("Kept", "struct Kept {}"), ("Changed", "struct Changed {}")]),
+        enums: rendered(&[("Removed", "enum Removed {}")]),
+        ..Default::default()
+    };
+
+    let current = GeneratedSwiftTypesStrings {
+        structs: rendered(&[
+            ("Kept", "struct Kept {}"),
+            ("Changed", "struct Changed { var a: Int }"),
+        ]),
+        protos: rendered(&[("Added", "typealias Added = TW_Added_Proto")]),
+        ..Default::default()
+    };
+
+    let diff = current.diff(&previous);
+    assert!(!diff.is_empty());
+    assert_eq!(diff.added.protos, vec!["Added"]);
+    assert_eq!(diff.removed.enums, vec!["Removed"]);
+    assert_eq!(diff.modified.structs, vec!["Changed"]);
+    assert!(diff.added.structs.is_empty());
+    assert!(diff.removed.structs.is_empty());
+
+    // No changes compared to itself.
+    assert!(current.diff(&current).is_empty());
+}
+
+#[test]
+fn generated_types_diff_against_dir() {
+    const INPUT: &str = include_str!("samples/enum_extension.input.yaml");
+
+    let rendered = render_to_strings(create_intput(INPUT)).unwrap();
+
+    // Write the bindings with the same layout as the `swift` command.
+    let dir = create_temp_dir("diff");
+    fs::create_dir_all(dir.join("Enums")).unwrap();
+    for (name, content) in &rendered.enums {
+        fs::write(dir.join(format!("Enums/{name}.swift")), content).unwrap();
+    }
+    for (name, content) in &rendered.extensions {
+        fs::write(dir.join(format!("{name}+Extension.swift")), content).unwrap();
+    }
+
+    let previous = GeneratedSwiftTypesStrings::read_from_dir(&dir).unwrap();
+    assert_eq!(previous.index(), rendered.index());
+    assert!(rendered.diff(&previous).is_empty());
+
+    // Modify the extension on disk.
+    let (name, _) = &rendered.extensions[0];
+    fs::write(dir.join(format!("{name}+Extension.swift")), "// Outdated.").unwrap();
+
+    let previous = GeneratedSwiftTypesStrings::read_from_dir(&dir).unwrap();
+    let diff = rendered.diff(&previous);
+    assert_eq!(diff.modified.extensions, vec![name.clone()]);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Records the calls of the codegen driver.
+#[derive(Default)]
+struct MockTarget {
+    calls: RefCell<Vec<String>>,
+}
+
+impl CodegenTarget for MockTarget {
+    fn map_type(&self, ty: &TypeVariant) -> String {
+        format!("{ty:?}")
+    }
+
+    fn render_struct(&self, strct: &StructInfo) -> crate::Result<Option<(String, String)>> {
+        self.calls
+            .borrow_mut()
+            .push(format!("struct {}", strct.name));
+        Ok(Some((strct.name.clone(), "rendered struct".to_string())))
+    }
+
+    fn render_enum(&self, enm: &EnumInfo) -> crate::Result<Option<(String, String)>> {
+        self.calls.borrow_mut().push(format!("enum {}", enm.name));
+        // Skip rendering.
+        Ok(None)
+    }
+}
+
+#[test]
+fn codegen_target_driver() {
+    const INPUT: &str = r#"
+name: Driver
+structs:
+- name: FirstStruct
+  is_public: true
+  is_class: false
+- name: SecondStruct
+  is_public: true
+  is_class: true
+enums:
+- name: MainEnum
+  is_public: true
+  value_type:
+    variant: u_int32_t
+  variants:
+  - name: one
+    value: 0
+"#;
+
+    let file_info = parse_str(INPUT).unwrap();
+    let target = MockTarget::default();
+
+    let rendered = render_file_info(&target, &file_info).unwrap();
+    assert_eq!(
+        *target.calls.borrow(),
+        ["struct FirstStruct", "struct SecondStruct", "enum MainEnum"]
+    );
+    assert_eq!(rendered.structs.len(), 2);
+    assert_eq!(rendered.structs[1].0, "SecondStruct");
+    assert!(rendered.enums.is_empty());
+}
+
+#[test]
+fn manifest_json_schema() {
+    let schema = json_schema();
+    let defs = &schema["$defs"];
+
+    assert_eq!(schema["$ref"], "#/$defs/FileInfo");
+    assert_eq!(defs["FileInfo"]["required"], serde_json::json!(["name"]));
+
+    // `TypeVariant` is a tagged union of `{ variant }` and `{ variant, value }`.
+    let arms = defs["TypeVariant"]["oneOf"].as_array().unwrap();
+    assert_eq!(arms.len(), 2);
+
+    let unit_variants = arms[0]["properties"]["variant"]["enum"].as_array().unwrap();
+    assert_eq!(unit_variants.len(), 20);
+    assert!(unit_variants.contains(&serde_json::json!("u_int32_t")));
+    assert!(unit_variants.contains(&serde_json::json!("size_t")));
+    assert!(!unit_variants.contains(&serde_json::json!("struct")));
+
+    assert_eq!(arms[1]["required"], serde_json::json!(["variant", "value"]));
+    assert_eq!(
+        arms[1]["properties"]["variant"]["enum"],
+        serde_json::json!(["struct", "enum"])
+    );
+
+    // The serialized form of a custom type matches the tagged arm.
+    let ty = serde_json::to_value(TypeVariant::Struct("TWFoo".to_string())).unwrap();
+    assert_eq!(
+        ty,
+        serde_json::json!({ "variant": "struct", "value": "TWFoo" })
+    );
+
+    // Type infos flatten the variant.
+    assert_eq!(defs["TypeInfo"]["allOf"][0]["$ref"], "#/$defs/TypeVariant");
+}
+
+#[test]
+fn file_info_json_round_trip() {
+    let dir = create_temp_dir("file_info_json_round_trip");
+
+    let class = parse_str(include_str!("samples/class.input.yaml")).unwrap();
+    let strct = parse_str(include_str!("samples/struct.input.yaml")).unwrap();
+    let enm = parse_str(include_str!("samples/enum.input.yaml")).unwrap();
+
+    // Single file info.
+    let path = dir.join("class.json");
+    class.write_to(&path).unwrap();
+    assert_eq!(FileInfo::read_from(&path).unwrap(), class);
+
+    // Whole manifest.
+    let file_infos = vec![class, strct, enm];
+    let path = dir.join("manifest.json");
+    write_manifest_json(&path, &file_infos).unwrap();
+    assert_eq!(read_manifest_json(&path).unwrap(), file_infos);
+
+    // Unsupported version.
+    let path = dir.join("version.json");
+    fs::write(&path, r#"{ "version": 2, "data": { "name": "Empty" } }"#).unwrap();
+    let err = FileInfo::read_from(&path).unwrap_err();
+    assert!(matches!(err, Error::BadFormat(_)));
+
+    // Bad shape: a single file info is not a manifest.
+    let path = dir.join("shape.json");
+    fs::write(&path, r#"{ "version": 1, "data": { "name": "Empty" } }"#).unwrap();
+    FileInfo::read_from(&path).unwrap();
+    let err = read_manifest_json(&path).unwrap_err();
+    assert!(matches!(err, Error::BadFormat(_)));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright © 2017 Trust Wallet.
+//
+// This is a GENERATED FILE, changes made here WILL BE LOST.
+//
+
+import Foundation
+
+public struct MainStruct {
+    init() {}
+
+    public static var staticProperty: Bool {
+        let result = MainStructStaticProperty()
+        return result
+    }
+
+    public var instanceProperty: Bool {
+        let obj = self.rawValue
+        let result = MainStructInstanceProperty(obj)
+        return result
+    }
+}
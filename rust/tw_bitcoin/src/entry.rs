@@ -155,6 +155,12 @@ impl BitcoinEntry {
             .map(crate::modules::transactions::OutputBuilder::utxo_from_proto)
             .collect::<Result<Vec<_>>>()?;
 
+        // Zero-value outputs are only allowed for OP_RETURN. Note that this
+        // does not apply to the change output, whose value is set for us.
+        utxo_outputs
+            .iter()
+            .try_for_each(crate::modules::transactions::OutputBuilder::validate_value)?;
+
         // If automatic change output creation is enabled (by default), a change
         // script must be provided.
         let change_script_pubkey = if proto.disable_change_output {
@@ -210,6 +216,10 @@ impl BitcoinEntry {
                 script_pubkey: change_output.script_pubkey.to_vec().into(),
                 control_block: Default::default(),
                 taproot_payload: Default::default(),
+                label: Default::default(),
+                min_locktime: Default::default(),
+                min_sequence: Default::default(),
+                sighash_hint: Default::default(),
             })
         }
 
@@ -261,6 +271,7 @@ impl BitcoinEntry {
         let mut utxo_outputs = vec![];
         for output in &proto.outputs {
             let utxo = crate::modules::transactions::OutputBuilder::utxo_from_proto(output)?;
+            crate::modules::transactions::OutputBuilder::validate_value(&utxo)?;
 
             utxo_outputs.push(utxo);
         }
@@ -66,6 +66,7 @@ pub fn taproot_build_and_sign_transaction(
         outputs.push(Proto::Output {
             value: output.amount as u64,
             to_recipient: ProtoOutputRecipient::custom_script_pubkey(output.script),
+            ..Default::default()
         })
     }
 
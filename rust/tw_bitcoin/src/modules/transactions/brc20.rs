@@ -1,6 +1,7 @@
 use super::ordinals::OrdinalsInscription;
 use crate::{Error, Result};
-use bitcoin::PublicKey;
+use bitcoin::{PublicKey, Witness};
+use secp256k1::{Parity, XOnlyPublicKey};
 use serde::Serialize;
 use tw_proto::BitcoinV2::Proto;
 
@@ -16,6 +17,17 @@ impl Brc20Ticker {
 
         Ok(Brc20Ticker(string))
     }
+    /// Creates a lowercase ticker. Most indexers (such as the reference BRC20
+    /// indexer and UniSat) treat tickers case-insensitively and use the
+    /// lowercase form, so `"Ordi"` and `"ordi"` refer to the same token. Use
+    /// [`Brc20Ticker::new`] to keep the exact casing.
+    ///
+    /// The length is validated on the original string.
+    pub fn new_normalized(string: String) -> Result<Self> {
+        let ticker = Self::new(string)?;
+
+        Ok(Brc20Ticker(ticker.0.to_lowercase()))
+    }
 }
 
 #[derive(Serialize)]
@@ -51,6 +63,21 @@ impl BRC20TransferPayload {
 pub struct BRC20TransferInscription(OrdinalsInscription);
 
 impl BRC20TransferInscription {
+    /// Parses the recipient (`inscribe_to`) of a BRC20 inscription, which must
+    /// be a compressed (33 bytes) or an x-only (32 bytes) public key.
+    pub fn recipient_from_slice(slice: &[u8]) -> Result<PublicKey> {
+        match slice.len() {
+            32 => {
+                let xonly = XOnlyPublicKey::from_slice(slice)
+                    .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+                Ok(PublicKey::new(xonly.public_key(Parity::Even)))
+            },
+            33 => Ok(PublicKey::from_slice(slice)?),
+            65 => Err(Error::from(Proto::Error::Error_uncompressed_public_key)),
+            _ => Err(Error::from(Proto::Error::Error_invalid_public_key)),
+        }
+    }
+
     pub fn new(
         recipient: PublicKey,
         ticker: Brc20Ticker,
@@ -66,9 +93,28 @@ impl BRC20TransferInscription {
 
         Ok(BRC20TransferInscription(inscription))
     }
+
     pub fn inscription(&self) -> &OrdinalsInscription {
         &self.0
     }
+
+    /// Estimates the virtual size (vbytes) of the reveal transaction, see
+    /// [`OrdinalsInscription::estimated_reveal_vbytes`].
+    pub fn estimated_reveal_vbytes(&self) -> u64 {
+        self.0.estimated_reveal_vbytes()
+    }
+
+    /// Assembles the witness of the reveal input for the given signature,
+    /// see [`OrdinalsInscription::reveal_witness`].
+    pub fn reveal_witness(&self, sig: &bitcoin::taproot::Signature) -> Witness {
+        self.0.reveal_witness(sig)
+    }
+
+    /// Returns the address of the commit output, see
+    /// [`OrdinalsInscription::commit_address`].
+    pub fn commit_address(&self, network: bitcoin::Network) -> Result<String> {
+        self.0.commit_address(network)
+    }
 }
 
 #[cfg(test)]
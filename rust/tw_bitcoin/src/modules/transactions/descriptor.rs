@@ -0,0 +1,189 @@
+use super::OutputBuilder;
+use crate::aliases::*;
+use crate::{Error, Result};
+use bitcoin::{PublicKey, ScriptBuf};
+use secp256k1::{Parity, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
+
+/// The characters allowed in an output descriptor, see BIP380.
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+/// The characters of the descriptor checksum.
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// The generator of the descriptor checksum (BCH code).
+const GENERATOR: [u64; 5] = [
+    0xf5dee51989,
+    0xa9fdca3312,
+    0x1bab10e32d,
+    0x3706b1677a,
+    0x644d626ffd,
+];
+
+impl OutputBuilder {
+    /// Creates the output for the given [output descriptor]. Supported are
+    /// `pkh(KEY)`, `wpkh(KEY)`, `sh(pkh(KEY))`, `sh(wpkh(KEY))` and
+    /// `tr(KEY)`, where `KEY` is a hex-encoded public key (x-only keys are
+    /// accepted for `tr`). The checksum is optional, but verified if present.
+    ///
+    /// Extended keys, key origins, multisig and Taproot script trees are not
+    /// supported and result in `Error_unsupported_descriptor`.
+    ///
+    /// [output descriptor]: https://github.com/bitcoin/bips/blob/master/bip-0380.mediawiki
+    pub fn from_descriptor(desc: &str, value: u64) -> Result<Proto::Output<'static>> {
+        let desc = strip_checksum(desc)?;
+
+        let variant = match split_expression(desc)? {
+            ("pkh", key) => ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(parse_key(key)?.to_bytes().into()),
+            }),
+            ("wpkh", key) => {
+                let pubkey = parse_compressed_key(key)?;
+
+                ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(pubkey.to_bytes().into()),
+                })
+            },
+            ("sh", inner) => {
+                let redeem_script = match split_expression(inner)? {
+                    ("pkh", key) => ScriptBuf::new_p2pkh(&parse_key(key)?.pubkey_hash()),
+                    ("wpkh", key) => {
+                        let pubkey = parse_compressed_key(key)?;
+                        let wpubkey_hash = pubkey
+                            .wpubkey_hash()
+                            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_descriptor))?;
+
+                        ScriptBuf::new_v0_p2wpkh(&wpubkey_hash)
+                    },
+                    _ => return Err(Error::from(Proto::Error::Error_unsupported_descriptor)),
+                };
+
+                ProtoOutputBuilder::p2sh(Proto::mod_Output::OutputRedeemScriptOrHash {
+                    variant: ProtoOutputRedeemScriptOrHashBuilder::redeem_script(
+                        redeem_script.to_bytes().into(),
+                    ),
+                })
+            },
+            ("tr", key) => {
+                // X-only keys imply an even Y coordinate.
+                let pubkey = match XOnlyPublicKey::from_slice(&decode_key(key)?) {
+                    Ok(xonly) => PublicKey::new(xonly.public_key(Parity::Even)),
+                    Err(_) => parse_compressed_key(key)?,
+                };
+
+                ProtoOutputBuilder::p2tr_key_path(pubkey.to_bytes().into())
+            },
+            _ => return Err(Error::from(Proto::Error::Error_unsupported_descriptor)),
+        };
+
+        Ok(Proto::Output {
+            value,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant,
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+// Splits an expression such as `wpkh(KEY)` into its name and argument.
+fn split_expression(expr: &str) -> Result<(&str, &str)> {
+    let (name, rest) = expr
+        .split_once('(')
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_descriptor))?;
+
+    let arg = rest
+        .strip_suffix(')')
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_descriptor))?;
+
+    Ok((name, arg))
+}
+
+// Decodes a hex-encoded key. Anything that is not plain hex, such as extended
+// keys, key origins or additional arguments, is not supported.
+fn decode_key(key: &str) -> Result<Vec<u8>> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::from(Proto::Error::Error_unsupported_descriptor));
+    }
+
+    tw_encoding::hex::decode(key).map_err(|_| Error::from(Proto::Error::Error_invalid_descriptor))
+}
+
+fn parse_key(key: &str) -> Result<PublicKey> {
+    PublicKey::from_slice(&decode_key(key)?)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_descriptor))
+}
+
+fn parse_compressed_key(key: &str) -> Result<PublicKey> {
+    let pubkey = parse_key(key)?;
+    if !pubkey.compressed {
+        return Err(Error::from(Proto::Error::Error_invalid_descriptor));
+    }
+
+    Ok(pubkey)
+}
+
+// Strips the optional checksum (`#` followed by eight characters) from the
+// descriptor, verifying it if present.
+fn strip_checksum(desc: &str) -> Result<&str> {
+    let Some((body, checksum)) = desc.split_once('#') else {
+        return Ok(desc);
+    };
+
+    let expected = descriptor_checksum(body)
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_descriptor))?;
+
+    if checksum != expected {
+        return Err(Error::from(Proto::Error::Error_invalid_descriptor));
+    }
+
+    Ok(body)
+}
+
+// Computes the checksum of the given descriptor, as specified in BIP380.
+// Returns `None` if the descriptor contains invalid characters.
+fn descriptor_checksum(desc: &str) -> Option<String> {
+    let mut symbols = Vec::with_capacity(desc.len() * 2);
+    let mut groups = Vec::with_capacity(3);
+
+    for c in desc.chars() {
+        let value = INPUT_CHARSET.find(c)? as u64;
+        symbols.push(value & 31);
+        groups.push(value >> 5);
+
+        if groups.len() == 3 {
+            symbols.push(groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+
+    match groups.as_slice() {
+        [a] => symbols.push(*a),
+        [a, b] => symbols.push(a * 3 + b),
+        _ => {},
+    }
+
+    symbols.extend([0; 8]);
+    let checksum = polymod(&symbols) ^ 1;
+
+    let checksum = (0..8)
+        .map(|i| CHECKSUM_CHARSET[((checksum >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect();
+
+    Some(checksum)
+}
+
+fn polymod(symbols: &[u64]) -> u64 {
+    let mut chk = 1;
+    for value in symbols {
+        let top = chk >> 35;
+        chk = ((chk & 0x7ffffffff) << 5) ^ value;
+
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+
+    chk
+}
@@ -0,0 +1,61 @@
+use super::RecipientKind;
+
+/// The minimum output values (in satoshis) per script type. Outputs below the
+/// threshold are considered dust.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DustThresholds {
+    pub p2pkh: u64,
+    pub p2sh: u64,
+    pub p2wpkh: u64,
+    pub p2wsh: u64,
+    pub p2tr: u64,
+    /// Applies to bare multisig and non-standard scripts.
+    pub other: u64,
+}
+
+impl DustThresholds {
+    /// The thresholds of Bitcoin Core's default relay policy, based on the
+    /// dust relay fee of 3 sat/vB.
+    pub const BITCOIN_CORE: DustThresholds = DustThresholds {
+        p2pkh: 546,
+        p2sh: 540,
+        p2wpkh: 294,
+        p2wsh: 330,
+        p2tr: 330,
+        other: 546,
+    };
+}
+
+/// The dust policy applied when validating outputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DustPolicy {
+    /// Bitcoin Core's default relay policy.
+    #[default]
+    BitcoinCore,
+    /// No dust checks at all, e.g. for regtest or test setups.
+    Disabled,
+    /// Custom thresholds per script type.
+    Custom(DustThresholds),
+}
+
+impl DustPolicy {
+    /// Returns the dust threshold for the given kind of script. OP_RETURN
+    /// outputs are unspendable and therefore never considered dust.
+    pub fn threshold(&self, kind: RecipientKind) -> u64 {
+        let thresholds = match self {
+            DustPolicy::BitcoinCore => DustThresholds::BITCOIN_CORE,
+            DustPolicy::Disabled => return 0,
+            DustPolicy::Custom(thresholds) => *thresholds,
+        };
+
+        match kind {
+            RecipientKind::P2pkh => thresholds.p2pkh,
+            RecipientKind::P2sh => thresholds.p2sh,
+            RecipientKind::P2wpkh => thresholds.p2wpkh,
+            RecipientKind::P2wsh => thresholds.p2wsh,
+            RecipientKind::P2tr => thresholds.p2tr,
+            RecipientKind::OpReturn => 0,
+            RecipientKind::Multisig { .. } | RecipientKind::NonStandard => thresholds.other,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use super::OutputBuilder;
+use crate::{Error, Result};
+use bitcoin::{PubkeyHash, ScriptBuf, ScriptHash};
+use secp256k1::hashes::Hash;
+use secp256k1::PublicKey;
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// The address prefixes of an Elements based network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElementsParams {
+    pub blinded_prefix: u8,
+    pub p2pkh_prefix: u8,
+    pub p2sh_prefix: u8,
+}
+
+impl ElementsParams {
+    pub const LIQUID: ElementsParams = ElementsParams {
+        blinded_prefix: 12,
+        p2pkh_prefix: 57,
+        p2sh_prefix: 39,
+    };
+    pub const LIQUID_TESTNET: ElementsParams = ElementsParams {
+        blinded_prefix: 23,
+        p2pkh_prefix: 36,
+        p2sh_prefix: 19,
+    };
+    pub const ELEMENTS_REGTEST: ElementsParams = ElementsParams {
+        blinded_prefix: 4,
+        p2pkh_prefix: 235,
+        p2sh_prefix: 75,
+    };
+
+    const ALL: [ElementsParams; 3] = [
+        ElementsParams::LIQUID,
+        ElementsParams::LIQUID_TESTNET,
+        ElementsParams::ELEMENTS_REGTEST,
+    ];
+}
+
+/// A (base58) confidential address of an Elements based network, such as
+/// Liquid. The address embeds the blinding public key next to the regular,
+/// unconfidential program.
+///
+/// Note that blech32 encoded (segwit) confidential addresses are not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfidentialAddress {
+    pub params: ElementsParams,
+    pub blinding_pubkey: PublicKey,
+    /// The unconfidential _scriptPubkey_.
+    pub script_pubkey: ScriptBuf,
+}
+
+impl FromStr for ConfidentialAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bad_address = || Error::from(Proto::Error::Error_bad_address_recipient);
+
+        // <blinded_prefix><prefix><blinding_pubkey (33 bytes)><hash (20 bytes)>
+        let data = bitcoin::base58::decode_check(s).map_err(|_| bad_address())?;
+        if data.len() != 55 {
+            return Err(bad_address());
+        }
+
+        let params = ElementsParams::ALL
+            .into_iter()
+            .find(|params| params.blinded_prefix == data[0])
+            .ok_or_else(bad_address)?;
+
+        let blinding_pubkey = PublicKey::from_slice(&data[2..35]).map_err(|_| bad_address())?;
+
+        let hash = &data[35..];
+        let script_pubkey = if data[1] == params.p2pkh_prefix {
+            ScriptBuf::new_p2pkh(&PubkeyHash::from_slice(hash).map_err(|_| bad_address())?)
+        } else if data[1] == params.p2sh_prefix {
+            ScriptBuf::new_p2sh(&ScriptHash::from_slice(hash).map_err(|_| bad_address())?)
+        } else {
+            return Err(bad_address());
+        };
+
+        Ok(ConfidentialAddress {
+            params,
+            blinding_pubkey,
+            script_pubkey,
+        })
+    }
+}
+
+impl OutputBuilder {
+    /// Creates the unconfidential output for the given confidential address.
+    /// The returned blinding public key must be used for blinding the output.
+    pub fn elements_confidential(
+        value: u64,
+        address: &str,
+    ) -> Result<(Proto::mod_PreSigningOutput::TxOut<'static>, PublicKey)> {
+        let address = ConfidentialAddress::from_str(address)?;
+
+        let utxo = Proto::mod_PreSigningOutput::TxOut {
+            value,
+            script_pubkey: address.script_pubkey.to_vec().into(),
+            ..Default::default()
+        };
+
+        Ok((utxo, address.blinding_pubkey))
+    }
+}
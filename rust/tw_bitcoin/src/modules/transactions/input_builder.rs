@@ -1,10 +1,11 @@
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
 use crate::aliases::*;
-use crate::modules::transactions::OrdinalNftInscription;
+use crate::modules::transactions::{InscriptionContent, InscriptionId, OrdinalNftInscription};
 use crate::{Error, Result};
-use bitcoin::taproot::{LeafVersion, TapLeafHash};
+use bitcoin::taproot::{ControlBlock, TapLeafHash};
 use bitcoin::ScriptBuf;
 use secp256k1::XOnlyPublicKey;
+use std::str::FromStr;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Utxo::Proto as UtxoProto;
@@ -132,9 +133,14 @@ impl InputBuilder {
                 },
                 ProtoInputBuilder::p2tr_script_path(complex) => {
                     let script_pubkey = ScriptBuf::from_bytes(complex.payload.to_vec());
+
+                    // The leaf version is encoded in the control block.
+                    let control_block = ControlBlock::decode(complex.control_block.as_ref())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+
                     let leaf_hash = Some(TapLeafHash::from_script(
                         script_pubkey.as_script(),
-                        bitcoin::taproot::LeafVersion::TapScript,
+                        control_block.leaf_version,
                     ));
 
                     let signing_method = if complex.one_prevout {
@@ -161,23 +167,20 @@ impl InputBuilder {
                     let mime_type = ordinal.mime_type.as_ref();
                     let data = ordinal.payload.as_ref();
 
-                    let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, pubkey)
-                        .expect("badly constructed Ordinal inscription");
+                    let mut content = InscriptionContent::new(mime_type.as_bytes(), data);
+                    if !ordinal.parent.is_empty() {
+                        content = content.with_parent(InscriptionId::from_str(&ordinal.parent)?);
+                    }
+
+                    let nft = OrdinalNftInscription::from_content(content, pubkey)?;
 
                     // We construct a control block to estimate the fee,
                     // otherwise we do not need it here.
-                    let control_block = nft
-                        .inscription()
-                        .spend_info()
-                        .control_block(&(
-                            nft.inscription().taproot_program().to_owned(),
-                            LeafVersion::TapScript,
-                        ))
-                        .expect("badly constructed control block");
+                    let control_block = nft.inscription().control_block();
 
                     let leaf_hash = Some(TapLeafHash::from_script(
                         nft.inscription().taproot_program(),
-                        bitcoin::taproot::LeafVersion::TapScript,
+                        control_block.leaf_version,
                     ));
 
                     let signing_method = if ordinal.one_prevout {
@@ -210,7 +213,8 @@ impl InputBuilder {
                 },
                 // TODO: Unify this and `ordinal_inscribe` somehow
                 ProtoInputBuilder::brc20_inscribe(brc20) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(brc20.inscribe_to.as_ref())?;
+                    let pubkey =
+                        BRC20TransferInscription::recipient_from_slice(brc20.inscribe_to.as_ref())?;
                     let ticker = Brc20Ticker::new(brc20.ticker.to_string())?;
 
                     let transfer = BRC20TransferInscription::new(
@@ -222,18 +226,11 @@ impl InputBuilder {
 
                     // We construct a control block to estimate the fee,
                     // otherwise we do not need it here.
-                    let control_block = transfer
-                        .inscription()
-                        .spend_info()
-                        .control_block(&(
-                            transfer.inscription().taproot_program().to_owned(),
-                            LeafVersion::TapScript,
-                        ))
-                        .expect("badly constructed control block");
+                    let control_block = transfer.inscription().control_block();
 
                     let leaf_hash = Some(TapLeafHash::from_script(
                         transfer.inscription().taproot_program(),
-                        bitcoin::taproot::LeafVersion::TapScript,
+                        control_block.leaf_version,
                     ));
 
                     let signing_method = if brc20.one_prevout {
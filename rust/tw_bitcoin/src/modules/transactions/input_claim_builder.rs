@@ -1,11 +1,12 @@
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
-use super::OrdinalNftInscription;
+use super::{InscriptionContent, InscriptionId, OrdinalNftInscription};
 use crate::aliases::*;
 use crate::{Error, Result};
 use bitcoin::consensus::Decodable;
-use bitcoin::taproot::{ControlBlock, LeafVersion};
+use bitcoin::taproot::{ControlBlock, TAPROOT_ANNEX_PREFIX};
 use bitcoin::{ScriptBuf, Witness};
 use std::borrow::Cow;
+use std::str::FromStr;
 use tw_coin_entry::coin_entry::SignatureBytes;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
@@ -74,31 +75,29 @@ impl InputClaimBuilder {
                     let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
 
                     // The spending script itself.
-                    (ScriptBuf::new(), {
-                        let mut w = Witness::new();
-                        w.push(sig.to_vec());
-                        w.push(taproot.payload.as_ref());
-                        w.push(control_block.serialize());
-                        w
-                    })
+                    let witness = Self::p2tr_script_path_witness(
+                        &sig,
+                        taproot.payload.as_ref(),
+                        &control_block,
+                        None,
+                    )?;
+
+                    (ScriptBuf::new(), witness)
                 },
                 ProtoInputBuilder::ordinal_inscribe(ordinal) => {
                     let pubkey = bitcoin::PublicKey::from_slice(ordinal.inscribe_to.as_ref())?;
                     let mime_type = ordinal.mime_type.as_ref();
                     let data = ordinal.payload.as_ref();
 
-                    let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, pubkey)
-                        .expect("badly constructed Ordinal inscription");
+                    let mut content = InscriptionContent::new(mime_type.as_bytes(), data);
+                    if !ordinal.parent.is_empty() {
+                        content = content.with_parent(InscriptionId::from_str(&ordinal.parent)?);
+                    }
+
+                    let nft = OrdinalNftInscription::from_content(content, pubkey)?;
 
                     // Create a control block for that inscription.
-                    let control_block = nft
-                        .inscription()
-                        .spend_info()
-                        .control_block(&(
-                            nft.inscription().taproot_program().to_owned(),
-                            LeafVersion::TapScript,
-                        ))
-                        .expect("badly constructed control block");
+                    let control_block = nft.inscription().control_block();
 
                     let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
 
@@ -112,7 +111,8 @@ impl InputClaimBuilder {
                     })
                 },
                 ProtoInputBuilder::brc20_inscribe(brc20) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(brc20.inscribe_to.as_ref())?;
+                    let pubkey =
+                        BRC20TransferInscription::recipient_from_slice(brc20.inscribe_to.as_ref())?;
                     let ticker = Brc20Ticker::new(brc20.ticker.to_string())?;
 
                     // Construct the BRC20 transfer inscription.
@@ -124,14 +124,7 @@ impl InputClaimBuilder {
                     .expect("invalid BRC20 transfer construction");
 
                     // Create a control block for that inscription.
-                    let control_block = transfer
-                        .inscription()
-                        .spend_info()
-                        .control_block(&(
-                            transfer.inscription().taproot_program().to_owned(),
-                            LeafVersion::TapScript,
-                        ))
-                        .expect("badly constructed control block");
+                    let control_block = transfer.inscription().control_block();
 
                     let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
 
@@ -173,4 +166,32 @@ impl InputClaimBuilder {
 
         Ok(claim)
     }
+    /// Assembles the witness of a P2TR script-path spend: the signature, the
+    /// revealed script and the control block. If an annex is provided, it's
+    /// appended as the last witness element and must start with the annex
+    /// prefix (`0x50`).
+    ///
+    /// Note that the annex is committed to by the Taproot sighash, hence the
+    /// signature must have been created with the very same annex.
+    pub fn p2tr_script_path_witness(
+        sig: &bitcoin::taproot::Signature,
+        payload: &[u8],
+        control_block: &ControlBlock,
+        annex: Option<&[u8]>,
+    ) -> Result<Witness> {
+        let mut w = Witness::new();
+        w.push(sig.to_vec());
+        w.push(payload);
+        w.push(control_block.serialize());
+
+        if let Some(annex) = annex {
+            if annex.first() != Some(&TAPROOT_ANNEX_PREFIX) {
+                return Err(Error::from(Proto::Error::Error_invalid_taproot_annex));
+            }
+
+            w.push(annex);
+        }
+
+        Ok(w)
+    }
 }
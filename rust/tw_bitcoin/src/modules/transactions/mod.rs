@@ -1,19 +1,48 @@
+use crate::{Error, Result};
 use bitcoin::key::PublicKey;
 use bitcoin::script::ScriptBuf;
-use bitcoin::taproot::{TapNodeHash, TaprootSpendInfo};
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapNodeHash, TaprootBuilder, TaprootSpendInfo};
+use secp256k1::XOnlyPublicKey;
+use tw_proto::BitcoinV2::Proto;
 
 mod brc20;
+mod descriptor;
+mod dust;
+#[cfg(feature = "elements")]
+mod elements;
 mod input_builder;
 mod input_claim_builder;
+mod network;
 mod ordinals;
 mod output_builder;
+mod script_builder;
+#[cfg(feature = "silent-payments")]
+mod silent_payments;
+mod spend_info;
+mod standardness;
+mod tap_tree;
 
 // Re-exports
 pub use brc20::{BRC20TransferInscription, Brc20Ticker};
+pub use dust::{DustPolicy, DustThresholds};
+#[cfg(feature = "elements")]
+pub use elements::{ConfidentialAddress, ElementsParams};
 pub use input_builder::InputBuilder;
 pub use input_claim_builder::InputClaimBuilder;
-pub use ordinals::{OrdinalNftInscription, OrdinalsInscription};
-pub use output_builder::OutputBuilder;
+pub use network::BitcoinNetwork;
+pub use ordinals::{InscriptionContent, InscriptionId, OrdinalNftInscription, OrdinalsInscription};
+pub use output_builder::{AddressSet, InscriptionBatchError, OutputBuilder, RecipientKind};
+pub use spend_info::SpendInfo;
+pub use standardness::StandardnessPolicy;
+pub use tap_tree::{TapTree, TapTreeLeaf};
+
+/// The BIP341 "nothing up my sleeve" (NUMS) point `H`, the SHA256 hash of the
+/// uncompressed generator point. Its discrete logarithm is unknown, so using it
+/// as the internal key makes the key-path provably unspendable.
+pub const NUMS_INTERNAL_KEY: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
 
 pub struct TaprootScript {
     pub pubkey: PublicKey,
@@ -23,4 +52,51 @@ pub struct TaprootScript {
 pub struct TaprootProgram {
     pub script: ScriptBuf,
     pub spend_info: TaprootSpendInfo,
+    pub leaf_version: LeafVersion,
+}
+
+impl TaprootProgram {
+    /// Creates a Taproot program with a single leaf containing the given
+    /// script. Per BIP341, the leaf version must be even and must not equal
+    /// the annex prefix (`0x50`); `0xc0` denotes Tapscript.
+    pub fn new(
+        script: ScriptBuf,
+        internal_key: XOnlyPublicKey,
+        leaf_version: u8,
+    ) -> Result<TaprootProgram> {
+        let leaf_version = LeafVersion::from_consensus(leaf_version)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_leaf_version))?;
+
+        let spend_info = TaprootBuilder::new()
+            .add_leaf_with_ver(0, script.clone(), leaf_version)
+            .expect("Taproot spending info with a single leaf must always build")
+            .finalize(&secp256k1::Secp256k1::new(), internal_key)
+            .expect("Taproot spending info with a single leaf must always build");
+
+        Ok(TaprootProgram {
+            script,
+            spend_info,
+            leaf_version,
+        })
+    }
+
+    /// Creates the control block for spending the leaf via the script-path.
+    /// The leaf version is encoded in the first byte of the serialized
+    /// control block.
+    pub fn control_block(&self) -> ControlBlock {
+        self.spend_info
+            .control_block(&(self.script.clone(), self.leaf_version))
+            .expect("the leaf must always be part of the Taproot tree")
+    }
+
+    /// Exports the (single leaf) script tree, e.g. for persisting it in the
+    /// BIP371 format.
+    pub fn tap_tree(&self) -> TapTree {
+        TapTree::new(vec![TapTreeLeaf {
+            depth: 0,
+            leaf_version: self.leaf_version,
+            script: self.script.clone(),
+        }])
+        .expect("a single leaf at depth 0 is always a complete tree")
+    }
 }
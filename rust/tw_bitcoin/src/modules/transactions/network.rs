@@ -0,0 +1,37 @@
+/// The network an address is expected to belong to.
+///
+/// This extends [`bitcoin::Network`] with networks the underlying crate is not
+/// aware of (yet), such as testnet4 (BIP94).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitcoinNetwork {
+    #[default]
+    Bitcoin,
+    Testnet,
+    /// Testnet4 shares the address prefixes of testnet3 (`tb1`, `m`/`n`,
+    /// `2`), but uses a distinct network magic.
+    Testnet4,
+    Signet,
+    Regtest,
+}
+
+impl BitcoinNetwork {
+    /// The network whose address prefixes are used by this network.
+    pub fn address_network(&self) -> bitcoin::Network {
+        match self {
+            BitcoinNetwork::Bitcoin => bitcoin::Network::Bitcoin,
+            BitcoinNetwork::Testnet | BitcoinNetwork::Testnet4 => bitcoin::Network::Testnet,
+            BitcoinNetwork::Signet => bitcoin::Network::Signet,
+            BitcoinNetwork::Regtest => bitcoin::Network::Regtest,
+        }
+    }
+    /// The network magic, as used in the P2P message headers.
+    pub fn magic(&self) -> [u8; 4] {
+        match self {
+            BitcoinNetwork::Bitcoin => [0xf9, 0xbe, 0xb4, 0xd9],
+            BitcoinNetwork::Testnet => [0x0b, 0x11, 0x09, 0x07],
+            BitcoinNetwork::Testnet4 => [0x1c, 0x16, 0x3f, 0x28],
+            BitcoinNetwork::Signet => [0x0a, 0x03, 0xcf, 0x40],
+            BitcoinNetwork::Regtest => [0xfa, 0xbf, 0xb5, 0xda],
+        }
+    }
+}
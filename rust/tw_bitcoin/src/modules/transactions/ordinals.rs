@@ -1,11 +1,102 @@
-use super::TaprootProgram;
+use super::{TapTree, TaprootProgram};
 use crate::{Error, Result};
-use bitcoin::script::{PushBytesBuf, ScriptBuf};
+use bitcoin::hashes::Hash;
+use bitcoin::script::{self, PushBytesBuf, ScriptBuf};
 use bitcoin::secp256k1::XOnlyPublicKey;
-use bitcoin::taproot::{TaprootBuilder, TaprootSpendInfo};
-use bitcoin::{PublicKey, Script};
+use bitcoin::taproot::{ControlBlock, TaprootSpendInfo, TAPROOT_LEAF_TAPSCRIPT};
+use bitcoin::{PublicKey, Script, Txid, Witness};
+use std::str::FromStr;
 use tw_proto::BitcoinV2::Proto;
 
+/// The envelope tag of the parent inscription.
+const PARENT_TAG: [u8; 1] = [3];
+/// The envelope tag of the (CBOR encoded) inscription metadata.
+const METADATA_TAG: [u8; 1] = [5];
+/// The envelope tag of the inscription metaprotocol.
+const METAPROTOCOL_TAG: [u8; 1] = [7];
+
+/// The ID of an inscription, formatted as `<txid>i<index>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InscriptionId {
+    pub txid: Txid,
+    pub index: u32,
+}
+
+impl InscriptionId {
+    /// Serializes the ID as used in envelope fields: the 32-byte txid
+    /// followed by the little-endian index, with trailing zeroes omitted.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = self.txid.to_byte_array().to_vec();
+        serialized.extend_from_slice(&self.index.to_le_bytes());
+
+        while serialized.len() > 32 && serialized.last() == Some(&0) {
+            serialized.pop();
+        }
+
+        serialized
+    }
+}
+
+impl FromStr for InscriptionId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (txid, index) = s
+            .split_once('i')
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_inscription_id))?;
+
+        let txid = Txid::from_str(txid)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_inscription_id))?;
+
+        // Only plain decimal indices are accepted, e.g. no leading `+`.
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::from(Proto::Error::Error_invalid_inscription_id));
+        }
+        let index = index
+            .parse::<u32>()
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_inscription_id))?;
+
+        Ok(InscriptionId { txid, index })
+    }
+}
+
+/// The content of a single inscription.
+#[derive(Debug, Clone, Copy)]
+pub struct InscriptionContent<'a> {
+    pub mime_type: &'a [u8],
+    pub payload: &'a [u8],
+    /// Optional, CBOR encoded metadata.
+    pub metadata: Option<&'a [u8]>,
+    /// Optional metaprotocol the inscription conforms to.
+    pub metaprotocol: Option<&'a str>,
+    /// Optional parent inscription, establishing provenance.
+    pub parent: Option<InscriptionId>,
+}
+
+impl<'a> InscriptionContent<'a> {
+    pub fn new(mime_type: &'a [u8], payload: &'a [u8]) -> Self {
+        InscriptionContent {
+            mime_type,
+            payload,
+            metadata: None,
+            metaprotocol: None,
+            parent: None,
+        }
+    }
+    pub fn with_metadata(mut self, metadata: &'a [u8]) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+    pub fn with_metaprotocol(mut self, metaprotocol: &'a str) -> Self {
+        self.metaprotocol = Some(metaprotocol);
+        self
+    }
+    pub fn with_parent(mut self, parent: InscriptionId) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+}
+
 pub struct OrdinalsInscription {
     envelope: TaprootProgram,
 }
@@ -18,12 +109,90 @@ impl OrdinalsInscription {
 
         Ok(OrdinalsInscription { envelope })
     }
+
+    /// Creates a batch of Ordinals Inscriptions, stacking one envelope per
+    /// content into a single Taproot script ("commit stage").
+    pub fn new_batch(
+        contents: &[InscriptionContent],
+        recipient: PublicKey,
+    ) -> Result<OrdinalsInscription> {
+        let envelope = create_batch_envelope(contents, recipient)?;
+
+        Ok(OrdinalsInscription { envelope })
+    }
+
     pub fn taproot_program(&self) -> &Script {
         self.envelope.script.as_script()
     }
+
     pub fn spend_info(&self) -> &TaprootSpendInfo {
         &self.envelope.spend_info
     }
+
+    /// The script tree of the commit output, required for revealing the
+    /// inscription later on.
+    pub fn tap_tree(&self) -> TapTree {
+        self.envelope.tap_tree()
+    }
+
+    /// The control block for revealing the inscription via the script-path.
+    pub fn control_block(&self) -> ControlBlock {
+        self.envelope.control_block()
+    }
+
+    /// Returns the (bech32m) address of the commit output, i.e. the P2TR
+    /// output committing to the inscription, to which the funds must be
+    /// sent before the inscription can be revealed.
+    pub fn commit_address(&self, network: bitcoin::Network) -> Result<String> {
+        let output_key = self.spend_info().output_key();
+        let address = bitcoin::Address::p2tr_tweaked(output_key, network);
+
+        Ok(address.to_string())
+    }
+
+    /// Assembles the witness of the reveal input, which spends the commit
+    /// output via the script-path: `<signature> <script> <control block>`.
+    pub fn reveal_witness(&self, sig: &bitcoin::taproot::Signature) -> Witness {
+        Witness::from_slice(&[
+            sig.to_vec(),
+            self.taproot_program().to_bytes(),
+            self.control_block().serialize(),
+        ])
+    }
+
+    /// Estimates the virtual size (vbytes) of the reveal transaction, which
+    /// spends a single commit output via the script-path and pays to a
+    /// single P2TR output. The inscription script and the control block are
+    /// part of the witness and therefore benefit from the witness discount.
+    pub fn estimated_reveal_vbytes(&self) -> u64 {
+        let script = self.taproot_program();
+        let control_block = self.control_block();
+
+        // Version, input count, input (outpoint, empty scriptSig, sequence),
+        // output count, P2TR output, locktime.
+        let base_size: u64 = 4 + 1 + (36 + 1 + 4) + 1 + (8 + 1 + 34) + 4;
+
+        // Marker and flag, witness item count, Schnorr signature, the
+        // inscription script and the control block.
+        let witness_size = 2
+            + 1
+            + (1 + 64)
+            + (compact_size_len(script.len()) + script.len() as u64)
+            + (compact_size_len(control_block.size()) + control_block.size() as u64);
+
+        let weight = base_size * 4 + witness_size;
+        weight.div_ceil(4)
+    }
+}
+
+// Returns the length of the compact size prefix for the given length.
+fn compact_size_len(len: usize) -> u64 {
+    match len {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
 }
 
 /// Creates an [Ordinals Inscription](https://docs.ordinals.com/inscriptions.html).
@@ -42,17 +211,59 @@ impl OrdinalsInscription {
 /// recipient. Stage two, the `internal_key` is the claimer of the transaction
 /// (where the Inscription script is available in the Witness).
 fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<TaprootProgram> {
+    create_batch_envelope(&[InscriptionContent::new(mime, data)], internal_key)
+}
+
+/// Creates a single Taproot script containing one stacked envelope per
+/// inscription content, see [`create_envelope`].
+fn create_batch_envelope(
+    contents: &[InscriptionContent],
+    internal_key: PublicKey,
+) -> Result<TaprootProgram> {
+    if contents.is_empty() {
+        return Err(Error::from(Proto::Error::Error_missing_inscription));
+    }
+
+    let mut builder = ScriptBuf::builder();
+    for content in contents {
+        // An empty payload would result in a degenerate inscription.
+        if content.payload.is_empty() {
+            return Err(Error::from(Proto::Error::Error_empty_inscription_payload));
+        }
+
+        builder = push_envelope(builder, content)?;
+    }
+
+    // Finalize scripts.
+    let script = builder.into_script();
+
+    // Generate the necessary spending information. As mentioned in the
+    // documentation of `create_envelope`, this serves two purposes; setting
+    // the spending condition and actually claiming the spending condition.
+    TaprootProgram::new(
+        script,
+        XOnlyPublicKey::from(internal_key.inner),
+        TAPROOT_LEAF_TAPSCRIPT,
+    )
+}
+
+/// Pushes a single envelope (`OP_FALSE OP_IF ... OP_ENDIF`) containing the
+/// inscription content.
+fn push_envelope(
+    builder: script::Builder,
+    content: &InscriptionContent,
+) -> Result<script::Builder> {
     use bitcoin::opcodes::all::*;
     use bitcoin::opcodes::*;
 
     // Create MIME buffer.
     let mut mime_buf = PushBytesBuf::new();
     mime_buf
-        .extend_from_slice(mime)
+        .extend_from_slice(content.mime_type)
         .map_err(|_| Error::from(Proto::Error::Error_ordinal_mime_type_too_large))?;
 
     // Create an Ordinals Inscription.
-    let mut builder = ScriptBuf::builder()
+    let mut builder = builder
         .push_opcode(OP_FALSE)
         .push_opcode(OP_IF)
         .push_slice(b"ord")
@@ -67,12 +278,49 @@ fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<
         // inconsistent, it's the current requirement.
         .push_opcode(OP_PUSHBYTES_1)
         // MIME type identifying the data
-        .push_slice(mime_buf.as_push_bytes())
-        // Separator.
-        .push_opcode(OP_PUSHBYTES_0);
+        .push_slice(mime_buf.as_push_bytes());
+
+    // Optional parent inscription.
+    if let Some(parent) = content.parent {
+        let parent_buf =
+            PushBytesBuf::try_from(parent.serialize()).expect("inscription ID is at most 36 bytes");
+
+        builder = builder.push_slice(&PARENT_TAG).push_slice(parent_buf);
+    }
+
+    // Optional metadata, which must be valid CBOR. Like the data, it's pushed
+    // in chunks, each prefixed with the tag.
+    if let Some(metadata) = content.metadata {
+        tw_encoding::cbor::decode::<serde::de::IgnoredAny>(metadata)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_inscription_metadata))?;
+
+        for chunk in metadata.chunks(520) {
+            let mut metadata_buf = PushBytesBuf::new();
+            metadata_buf
+                .extend_from_slice(chunk)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_inscription_metadata))?;
+
+            builder = builder.push_slice(&METADATA_TAG).push_slice(metadata_buf);
+        }
+    }
+
+    // Optional metaprotocol.
+    if let Some(metaprotocol) = content.metaprotocol {
+        let mut metaprotocol_buf = PushBytesBuf::new();
+        metaprotocol_buf
+            .extend_from_slice(metaprotocol.as_bytes())
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_inscription_metadata))?;
+
+        builder = builder
+            .push_slice(&METAPROTOCOL_TAG)
+            .push_slice(metaprotocol_buf);
+    }
+
+    // Separator.
+    builder = builder.push_opcode(OP_PUSHBYTES_0);
 
     // Push the actual data in chunks.
-    for chunk in data.chunks(520) {
+    for chunk in content.payload.chunks(520) {
         // Create data buffer.
         let mut data_buf = PushBytesBuf::new();
         data_buf
@@ -83,23 +331,7 @@ fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<
         builder = builder.push_slice(data_buf);
     }
 
-    // Finalize scripts.
-    let script = builder.push_opcode(OP_ENDIF).into_script();
-
-    // Generate the necessary spending information. As mentioned in the
-    // documentation of this function at the top, this serves two purposes;
-    // setting the spending condition and actually claiming the spending
-    // condition.
-    let spend_info = TaprootBuilder::new()
-        .add_leaf(0, script.clone())
-        .expect("Ordinals Inscription spending info must always build")
-        .finalize(
-            &secp256k1::Secp256k1::new(),
-            XOnlyPublicKey::from(internal_key.inner),
-        )
-        .expect("Ordinals Inscription spending info must always build");
-
-    Ok(TaprootProgram { script, spend_info })
+    Ok(builder.push_opcode(OP_ENDIF))
 }
 
 pub struct OrdinalNftInscription(OrdinalsInscription);
@@ -119,7 +351,36 @@ impl OrdinalNftInscription {
     pub fn new(mime_type: &[u8], data: &[u8], recipient: PublicKey) -> Result<Self> {
         OrdinalsInscription::new(mime_type, data, recipient).map(OrdinalNftInscription)
     }
+
+    // Constructs multiple [Ordinal inscriptions] that are revealed in a single
+    // output.
+    //
+    // [Ordinal inscriptions]: https://docs.ordinals.com/inscriptions.html
+    pub fn new_batch(contents: &[InscriptionContent], recipient: PublicKey) -> Result<Self> {
+        OrdinalsInscription::new_batch(contents, recipient).map(OrdinalNftInscription)
+    }
+
+    // Constructs an [Ordinal inscription] from the given content, which can
+    // carry additional fields such as metadata or a metaprotocol.
+    //
+    // [Ordinal inscription]: https://docs.ordinals.com/inscriptions.html
+    pub fn from_content(content: InscriptionContent, recipient: PublicKey) -> Result<Self> {
+        Self::new_batch(&[content], recipient)
+    }
+
     pub fn inscription(&self) -> &OrdinalsInscription {
         &self.0
     }
+
+    /// Estimates the virtual size (vbytes) of the reveal transaction, see
+    /// [`OrdinalsInscription::estimated_reveal_vbytes`].
+    pub fn estimated_reveal_vbytes(&self) -> u64 {
+        self.0.estimated_reveal_vbytes()
+    }
+
+    /// Returns the address of the commit output, see
+    /// [`OrdinalsInscription::commit_address`].
+    pub fn commit_address(&self, network: bitcoin::Network) -> Result<String> {
+        self.0.commit_address(network)
+    }
 }
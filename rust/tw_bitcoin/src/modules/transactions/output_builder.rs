@@ -1,24 +1,92 @@
+use std::borrow::Cow;
 use std::str::FromStr;
 
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
-use super::OrdinalNftInscription;
+use super::script_builder::ScriptBuilder;
+use super::{
+    BitcoinNetwork, DustPolicy, InscriptionContent, InscriptionId, OrdinalNftInscription,
+    OrdinalsInscription, StandardnessPolicy, NUMS_INTERNAL_KEY,
+};
 use crate::aliases::*;
 use crate::{Error, Result};
 use bitcoin::address::{Payload, WitnessVersion};
-use bitcoin::key::TweakedPublicKey;
-use bitcoin::taproot::{LeafVersion, TapNodeHash};
-use bitcoin::{Address, PubkeyHash, ScriptBuf, ScriptHash, WPubkeyHash, WScriptHash};
+use bitcoin::bip32::{ChildNumber, ExtendedPubKey};
+use bitcoin::key::{TapTweak, TweakedPublicKey};
+use bitcoin::script::{Instruction, PushBytesBuf};
+use bitcoin::taproot::TapNodeHash;
+use bitcoin::{Address, PubkeyHash, Script, ScriptBuf, ScriptHash, WPubkeyHash, WScriptHash};
 use secp256k1::hashes::Hash;
-use secp256k1::XOnlyPublicKey;
+use secp256k1::{Parity, XOnlyPublicKey};
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 
 pub struct OutputBuilder;
 
+/// The kind of spending condition a script represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientKind {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    OpReturn,
+    /// Bare `m-of-n` multisig (`OP_CHECKMULTISIG`).
+    Multisig {
+        required: u8,
+        total: u8,
+    },
+    NonStandard,
+}
+
+/// The error of building a batch of inscription commit outputs, referring to
+/// the inscription that failed.
+#[derive(Debug)]
+pub struct InscriptionBatchError {
+    pub index: usize,
+    pub error: Error,
+}
+
+/// The standard addresses of a single public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressSet {
+    pub p2pkh: String,
+    pub p2wpkh: String,
+    /// P2WPKH nested in P2SH.
+    pub p2sh_p2wpkh: String,
+    /// The key-path only P2TR address (BIP86).
+    pub p2tr: String,
+}
+
 // Convenience varibles used solely for readability.
 const NO_CONTROL_BLOCK: Option<Vec<u8>> = None;
 const NO_TAPROOT_PAYLOAD: Option<Vec<u8>> = None;
 
+/// The pay-to-anchor (P2A) _scriptPubkey_: `OP_1 <0x4e73>`.
+const PAY_TO_ANCHOR_SCRIPT: &[u8] = &[0x51, 0x02, 0x4e, 0x73];
+
+/// The maximum size of a standard OP_RETURN _scriptPubkey_: `OP_RETURN`,
+/// `OP_PUSHDATA1 <len>` and 80 bytes of data.
+const MAX_STANDARD_OP_RETURN_SIZE: usize = 83;
+
+/// The canonical sequence number for signaling opt-in replace-by-fee.
+const RBF_SEQUENCE: u32 = 0xfffffffd;
+
+/// The maximum size of a standard P2WSH witness script.
+const MAX_STANDARD_P2WSH_SCRIPT_SIZE: usize = 3600;
+
+/// The maximum size of a taproot program revealed as a standard witness
+/// element: the standard transaction weight (400,000) less the remainder of a
+/// single-input, single-output reveal transaction (483 weight units).
+const MAX_STANDARD_TAPROOT_PROGRAM_SIZE: usize = 399_517;
+
+/// The maximum size of a script (`MAX_SCRIPT_SIZE` in Bitcoin Core).
+const MAX_SCRIPT_SIZE: usize = 10_000;
+
+/// The maximum length of an address (BIP173 limits bech32 strings to 90
+/// characters), checked before parsing untrusted input.
+const MAX_ADDRESS_LENGTH: usize = 90;
+
 impl OutputBuilder {
     /// Creates the spending condition (_scriptPubkey_) for a given output.
     pub fn utxo_from_proto(
@@ -78,12 +146,24 @@ impl OutputBuilder {
                     )
                 },
                 ProtoOutputBuilder::p2tr_script_path(complex) => {
+                    // The merkle root must be exactly 32 bytes.
                     let node_hash = TapNodeHash::from_slice(complex.merkle_root.as_ref())
                         .map_err(|_| Error::from(Proto::Error::Error_invalid_taproot_root))?;
 
-                    let pubkey = bitcoin::PublicKey::from_slice(complex.internal_key.as_ref())?;
-                    let xonly = XOnlyPublicKey::from(pubkey.inner);
+                    let xonly = if complex.internal_key.is_empty() && complex.nums_internal_key {
+                        XOnlyPublicKey::from_slice(&NUMS_INTERNAL_KEY)
+                            .expect("the NUMS point is a valid x-only public key")
+                    } else {
+                        let pubkey = bitcoin::PublicKey::from_slice(complex.internal_key.as_ref())
+                            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+                        XOnlyPublicKey::from(pubkey.inner)
+                    };
 
+                    // The internal key is tweaked with the merkle root. The
+                    // output therefore remains spendable via the key-path (by
+                    // signing with the equally tweaked private key) as well as
+                    // via the script-path (by revealing a leaf script and its
+                    // control block).
                     (
                         ScriptBuf::new_v1_p2tr(&secp, xonly, Some(node_hash)),
                         NO_CONTROL_BLOCK,
@@ -109,18 +189,16 @@ impl OutputBuilder {
                     let mime_type = ordinal.mime_type.as_ref();
                     let data = ordinal.payload.as_ref();
 
-                    let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, pubkey)
-                        .expect("badly constructed Ordinal inscription");
+                    let mut content = InscriptionContent::new(mime_type.as_bytes(), data);
+                    if !ordinal.parent.is_empty() {
+                        content = content.with_parent(InscriptionId::from_str(&ordinal.parent)?);
+                    }
+
+                    let nft = OrdinalNftInscription::from_content(content, pubkey)?;
+                    validate_program_size(nft.inscription())?;
 
                     // Construct the control block.
-                    let control_block = nft
-                        .inscription()
-                        .spend_info()
-                        .control_block(&(
-                            nft.inscription().taproot_program().to_owned(),
-                            LeafVersion::TapScript,
-                        ))
-                        .expect("badly constructed control block");
+                    let control_block = nft.inscription().control_block();
 
                     // Construct the merkle root.
                     let merkle_root = nft
@@ -136,7 +214,8 @@ impl OutputBuilder {
                     )
                 },
                 ProtoOutputBuilder::brc20_inscribe(brc20) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(brc20.inscribe_to.as_ref())?;
+                    let pubkey =
+                        BRC20TransferInscription::recipient_from_slice(brc20.inscribe_to.as_ref())?;
                     let xonly = XOnlyPublicKey::from(pubkey.inner);
 
                     let ticker = Brc20Ticker::new(brc20.ticker.to_string())?;
@@ -146,16 +225,10 @@ impl OutputBuilder {
                         brc20.transfer_amount.to_string(),
                     )
                     .expect("invalid BRC20 transfer construction");
+                    validate_program_size(transfer.inscription())?;
 
                     // Construct the control block.
-                    let control_block = transfer
-                        .inscription()
-                        .spend_info()
-                        .control_block(&(
-                            transfer.inscription().taproot_program().to_owned(),
-                            LeafVersion::TapScript,
-                        ))
-                        .expect("badly constructed control block");
+                    let control_block = transfer.inscription().control_block();
 
                     // Construct the merkle root.
                     let merkle_root = transfer
@@ -170,13 +243,43 @@ impl OutputBuilder {
                         Some(transfer.inscription().taproot_program().to_vec()),
                     )
                 },
+                ProtoOutputBuilder::op_return(op_return) => (
+                    op_return_script(&op_return.data)?,
+                    NO_CONTROL_BLOCK,
+                    NO_TAPROOT_PAYLOAD,
+                ),
+                ProtoOutputBuilder::witness_program(witness) => (
+                    witness_program_script(witness.version, &witness.program)?,
+                    NO_CONTROL_BLOCK,
+                    NO_TAPROOT_PAYLOAD,
+                ),
+                ProtoOutputBuilder::htlc(htlc) => {
+                    let witness_script = htlc_script(htlc)?;
+                    (
+                        ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash()),
+                        NO_CONTROL_BLOCK,
+                        NO_TAPROOT_PAYLOAD,
+                    )
+                },
+                #[cfg(feature = "silent-payments")]
+                ProtoOutputBuilder::silent_payment(payment) => (
+                    super::silent_payments::silent_payment_script(payment)?,
+                    NO_CONTROL_BLOCK,
+                    NO_TAPROOT_PAYLOAD,
+                ),
+                #[cfg(not(feature = "silent-payments"))]
+                ProtoOutputBuilder::silent_payment(_) => {
+                    return Err(Error::from(Proto::Error::Error_silent_payments_unsupported))
+                },
                 ProtoOutputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_output_builder))
                 },
             },
             // We derive the transaction type from the address.
             ProtoOutputRecipient::from_address(addr) => {
-                let proto = output_from_address(output.value, addr.as_ref())?;
+                let mut proto = output_from_address(output.value, addr.as_ref())?;
+                proto.label = output.label.to_string().into();
+                proto.sighash_hint = output.sighash_hint;
 
                 // Recursive call, will initiate the appropraite builder.
                 return Self::utxo_from_proto(&proto);
@@ -186,15 +289,702 @@ impl OutputBuilder {
             },
         };
 
+        // Timelock hints for the spender, derived from the known script.
+        let (min_locktime, min_sequence) = spending_script(&output.to_recipient)
+            .map(|script| timelock_hints(Script::from_bytes(&script)))
+            .unwrap_or_default();
+
+        // Sanity check (debug builds only): the built script must match the
+        // kind of the selected builder.
+        debug_assert!(
+            expected_recipient_kind(&output.to_recipient)
+                .map_or(true, |kind| Self::classify_script(script_pubkey.as_bytes())
+                    == kind),
+            "built scriptPubkey does not match the output builder"
+        );
+
         let utxo = Proto::mod_PreSigningOutput::TxOut {
             value: output.value,
             script_pubkey: script_pubkey.to_vec().into(),
             control_block: control_block.map(|cb| cb.into()).unwrap_or_default(),
             taproot_payload: taproot_payload.map(|cb| cb.into()).unwrap_or_default(),
+            label: output.label.to_string().into(),
+            min_locktime,
+            min_sequence,
+            sighash_hint: output.sighash_hint,
         };
 
         Ok(utxo)
     }
+
+    /// Detects the network the given address belongs to. Signet addresses
+    /// are encoded identically to testnet addresses and are therefore
+    /// reported as [`bitcoin::Network::Testnet`]. Base58 addresses of
+    /// testnet and regtest share the same prefixes, in which case
+    /// `Error_ambiguous_address_network` is returned.
+    pub fn detect_network(addr: &str) -> Result<bitcoin::Network> {
+        use bitcoin::address::NetworkUnchecked;
+
+        let addr = Address::<NetworkUnchecked>::from_str(addr)
+            .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
+
+        let mut matches = [
+            bitcoin::Network::Bitcoin,
+            bitcoin::Network::Testnet,
+            bitcoin::Network::Regtest,
+        ]
+        .into_iter()
+        .filter(|network| addr.is_valid_for_network(*network));
+
+        match (matches.next(), matches.next()) {
+            (Some(network), None) => Ok(network),
+            (Some(_), Some(_)) => Err(Error::from(Proto::Error::Error_ambiguous_address_network)),
+            (None, _) => Err(Error::from(Proto::Error::Error_bad_address_recipient)),
+        }
+    }
+
+    /// Serializes the built output in its consensus format: the value
+    /// (little-endian), followed by the length-prefixed _scriptPubkey_.
+    pub fn serialize_txout(txout: &Proto::mod_PreSigningOutput::TxOut<'_>) -> Vec<u8> {
+        let txout = bitcoin::TxOut {
+            value: txout.value,
+            script_pubkey: ScriptBuf::from_bytes(txout.script_pubkey.to_vec()),
+        };
+
+        bitcoin::consensus::serialize(&txout)
+    }
+
+    /// Validates that the value of a built output is not below the dust
+    /// threshold of the given policy.
+    pub fn validate_dust(
+        utxo: &Proto::mod_PreSigningOutput::TxOut<'_>,
+        policy: &DustPolicy,
+    ) -> Result<()> {
+        let kind = Self::classify_script(utxo.script_pubkey.as_ref());
+
+        if utxo.value < policy.threshold(kind) {
+            return Err(Error::from(Proto::Error::Error_dust_output));
+        }
+
+        Ok(())
+    }
+
+    /// Validates that the _scriptPubkey_ of a built output is standard under
+    /// the given policy, e.g. to catch hand-crafted scripts that exceed the
+    /// maximum script size or contain disabled opcodes.
+    pub fn validate_standardness(
+        utxo: &Proto::mod_PreSigningOutput::TxOut<'_>,
+        policy: &StandardnessPolicy,
+    ) -> Result<()> {
+        if !policy.is_strict() {
+            return Ok(());
+        }
+
+        let script = Script::from_bytes(utxo.script_pubkey.as_ref());
+
+        if script.len() > MAX_SCRIPT_SIZE {
+            return Err(Error::from(Proto::Error::Error_script_too_large));
+        }
+
+        for ins in script.instructions() {
+            // Malformed pushes are left to the consensus rules.
+            let Ok(Instruction::Op(op)) = ins else {
+                continue;
+            };
+
+            if is_disabled_opcode(op) {
+                return Err(Error::from(Proto::Error::Error_disabled_opcode));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds the outputs for all recipients, followed by the optional change
+    /// output, and returns them together with the total output value (e.g.
+    /// for calculating the fee). The change output must not pay to the same
+    /// script as one of the recipients. At least one output must be
+    /// spendable, i.e. not an OP_RETURN output.
+    pub fn build_output_set(
+        recipients: &[Proto::Output<'_>],
+        change: Option<&Proto::Output<'_>>,
+    ) -> Result<(Vec<Proto::mod_PreSigningOutput::TxOut<'static>>, u64)> {
+        let (utxos, total) = Self::build_data_output_set(recipients, change)?;
+
+        // A transaction consisting solely of OP_RETURN outputs is non-standard.
+        if !utxos.is_empty()
+            && utxos
+                .iter()
+                .all(|utxo| Self::classify_script(&utxo.script_pubkey) == RecipientKind::OpReturn)
+        {
+            return Err(Error::from(Proto::Error::Error_only_unspendable_outputs));
+        }
+
+        Ok((utxos, total))
+    }
+
+    /// Like [`OutputBuilder::build_output_set`], but allows for data-only
+    /// transactions, which consist solely of OP_RETURN outputs.
+    pub fn build_data_output_set(
+        recipients: &[Proto::Output<'_>],
+        change: Option<&Proto::Output<'_>>,
+    ) -> Result<(Vec<Proto::mod_PreSigningOutput::TxOut<'static>>, u64)> {
+        let mut utxos = recipients
+            .iter()
+            .map(Self::utxo_from_proto)
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(change) = change {
+            let change = Self::utxo_from_proto(change)?;
+
+            if utxos
+                .iter()
+                .any(|utxo| utxo.script_pubkey == change.script_pubkey)
+            {
+                return Err(Error::from(Proto::Error::Error_duplicate_change_output));
+            }
+
+            utxos.push(change);
+        }
+
+        let total = utxos.iter().try_fold(0u64, |total, utxo| {
+            total
+                .checked_add(utxo.value)
+                .ok_or_else(|| Error::from(Proto::Error::Error_output_value_overflow))
+        })?;
+
+        Ok((utxos, total))
+    }
+
+    /// Like [`OutputBuilder::build_output_set`], but recipients without a
+    /// recipient variant (`None`) are skipped rather than rejected, which
+    /// allows for sparse arrays of recipients.
+    pub fn build_sparse_output_set(
+        recipients: &[Proto::Output<'_>],
+        change: Option<&Proto::Output<'_>>,
+    ) -> Result<(Vec<Proto::mod_PreSigningOutput::TxOut<'static>>, u64)> {
+        let recipients = recipients
+            .iter()
+            .filter(|output| !matches!(output.to_recipient, ProtoOutputRecipient::None))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        Self::build_output_set(&recipients, change)
+    }
+
+    /// Creates the output for the given address, which must belong to the
+    /// given network. Mainnet addresses are rejected when parsing for a test
+    /// network and vice versa.
+    pub fn output_from_address_for_network(
+        value: u64,
+        addr: &str,
+        network: BitcoinNetwork,
+    ) -> Result<Proto::Output<'static>> {
+        output_from_address_for_network(value, addr, network)
+    }
+
+    /// Creates the output for the given recipient, which is expected to be
+    /// an address. If `allow_raw_script` is set and the recipient is not a
+    /// valid address, it is interpreted as a hex-encoded _scriptPubkey_
+    /// instead. This is opt-in, so that mistyped addresses are not silently
+    /// treated as scripts.
+    pub fn output_from_address_or_script(
+        value: u64,
+        recipient: &str,
+        allow_raw_script: bool,
+    ) -> Result<Proto::Output<'static>> {
+        let err = match output_from_address(value, recipient) {
+            Ok(output) => return Ok(output),
+            Err(err) if !allow_raw_script => return Err(err),
+            Err(err) => err,
+        };
+
+        let script = tw_encoding::hex::decode(recipient).map_err(|_| err)?;
+        let is_valid = !script.is_empty()
+            && Script::from_bytes(&script)
+                .instructions()
+                .all(|ins| ins.is_ok());
+
+        if !is_valid {
+            return Err(Error::from(Proto::Error::Error_bad_address_recipient));
+        }
+
+        Ok(Proto::Output {
+            value,
+            to_recipient: ProtoOutputRecipient::custom_script_pubkey(script.into()),
+            ..Default::default()
+        })
+    }
+
+    /// Builds one commit output per inscription, each paying `value` to the
+    /// P2TR output committing to the inscription for `recipient`. This allows
+    /// funding many reveals with a single commit transaction; the control
+    /// block and the inscription script required for each reveal are part of
+    /// the returned outputs. Fails with the index of the first invalid
+    /// inscription.
+    pub fn inscription_commit_outputs(
+        contents: &[InscriptionContent],
+        recipient: bitcoin::PublicKey,
+        value: u64,
+    ) -> std::result::Result<Vec<Proto::mod_PreSigningOutput::TxOut<'static>>, InscriptionBatchError>
+    {
+        let secp = secp256k1::Secp256k1::new();
+        let xonly = XOnlyPublicKey::from(recipient.inner);
+
+        contents
+            .iter()
+            .enumerate()
+            .map(|(index, content)| {
+                let nft = OrdinalNftInscription::from_content(*content, recipient)
+                    .and_then(|nft| validate_program_size(nft.inscription()).map(|_| nft))
+                    .map_err(|error| InscriptionBatchError { index, error })?;
+
+                let merkle_root = nft
+                    .inscription()
+                    .spend_info()
+                    .merkle_root()
+                    .expect("badly constructed Taproot merkle root");
+
+                Ok(Proto::mod_PreSigningOutput::TxOut {
+                    value,
+                    script_pubkey: ScriptBuf::new_v1_p2tr(&secp, xonly, Some(merkle_root))
+                        .to_vec()
+                        .into(),
+                    control_block: nft.inscription().control_block().serialize().into(),
+                    taproot_payload: nft.inscription().taproot_program().to_vec().into(),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Validates the value of a built output. OP_RETURN outputs must carry
+    /// exactly zero satoshis, while every other output (except for
+    /// pay-to-anchor outputs) must carry a positive amount.
+    pub fn validate_value(utxo: &Proto::mod_PreSigningOutput::TxOut<'_>) -> Result<()> {
+        let script = Script::from_bytes(utxo.script_pubkey.as_ref());
+
+        if script.is_op_return() {
+            if utxo.value != 0 {
+                return Err(Error::from(Proto::Error::Error_op_return_nonzero_value));
+            }
+        } else if utxo.value == 0 && script.as_bytes() != PAY_TO_ANCHOR_SCRIPT {
+            return Err(Error::from(Proto::Error::Error_zero_value_output));
+        }
+
+        Ok(())
+    }
+
+    /// Classifies the given script, such as a _scriptPubkey_ or a redeem
+    /// script.
+    pub fn classify_script(script: &[u8]) -> RecipientKind {
+        let script = Script::from_bytes(script);
+
+        if script.is_p2pkh() {
+            RecipientKind::P2pkh
+        } else if script.is_p2sh() {
+            RecipientKind::P2sh
+        } else if script.is_v0_p2wpkh() {
+            RecipientKind::P2wpkh
+        } else if script.is_v0_p2wsh() {
+            RecipientKind::P2wsh
+        } else if script.is_v1_p2tr() {
+            RecipientKind::P2tr
+        } else if script.is_op_return() {
+            RecipientKind::OpReturn
+        } else if let Some((required, total)) = multisig_params(script) {
+            RecipientKind::Multisig { required, total }
+        } else {
+            RecipientKind::NonStandard
+        }
+    }
+
+    /// Classifies the redeem script of a P2SH output, such as P2WPKH or P2WSH
+    /// nested in P2SH, or a multisig redeem script. The redeem script is
+    /// usually revealed in the scriptSig (or witness) of the spending input.
+    pub fn decode_p2sh_redeem(redeem_script: &[u8]) -> Result<RecipientKind> {
+        if redeem_script.is_empty() {
+            return Err(Error::from(Proto::Error::Error_invalid_redeem_script));
+        }
+
+        Ok(Self::classify_script(redeem_script))
+    }
+
+    /// Whether the given redeem script hashes to the given P2SH script hash,
+    /// e.g. to detect mismatching data before spending the output.
+    pub fn verify_p2sh_redeem(hash: &ScriptHash, redeem_script: &[u8]) -> bool {
+        Script::from_bytes(redeem_script).script_hash() == *hash
+    }
+
+    /// Returns the address a (standard) _scriptPubkey_ pays to, e.g. for
+    /// displaying the recipient of a built output. Scripts without an address
+    /// representation, such as OP_RETURN or bare multisig, are rejected.
+    pub fn address_from_script(script: &[u8], network: bitcoin::Network) -> Result<String> {
+        let address = Address::from_script(Script::from_bytes(script), network)
+            .map_err(|_| Error::from(Proto::Error::Error_unsupported_address_recipient))?;
+
+        Ok(address.to_string())
+    }
+
+    /// Checks whether the _scriptPubkey_ pays to the given address, e.g. to
+    /// confirm that a built P2TR output commits to the intended Taproot
+    /// address. All standard address types are supported.
+    pub fn script_matches_address(
+        script: &[u8],
+        address: &str,
+        network: bitcoin::Network,
+    ) -> Result<bool> {
+        let address = Address::from_str(address)
+            .map_err(address_error)?
+            .require_network(network)
+            .map_err(address_error)?;
+
+        Ok(address.script_pubkey().as_bytes() == script)
+    }
+
+    /// Returns all standard addresses (P2PKH, P2WPKH, P2SH-P2WPKH and P2TR
+    /// key-path) for the given public key, e.g. for displaying every address
+    /// type of a wallet. The public key must be compressed.
+    pub fn all_addresses(pubkey: &[u8], network: bitcoin::Network) -> Result<AddressSet> {
+        let pubkey = bitcoin::PublicKey::from_slice(pubkey)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+        // Segwit and Taproot require compressed public keys.
+        if !pubkey.compressed {
+            return Err(Error::from(Proto::Error::Error_invalid_public_key));
+        }
+
+        let secp = secp256k1::Secp256k1::new();
+        let (internal_key, _) = pubkey.inner.x_only_public_key();
+
+        let p2wpkh = Address::p2wpkh(&pubkey, network)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+        let p2sh_p2wpkh = Address::p2shwpkh(&pubkey, network)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+        Ok(AddressSet {
+            p2pkh: Address::p2pkh(&pubkey, network).to_string(),
+            p2wpkh: p2wpkh.to_string(),
+            p2sh_p2wpkh: p2sh_p2wpkh.to_string(),
+            p2tr: Address::p2tr(&secp, internal_key, None, network).to_string(),
+        })
+    }
+
+    /// Builds the watch-only receive output of the child key at the given
+    /// (non-hardened) index of the extended public key, i.e. without access
+    /// to the private key. Supported address types are P2PKH, P2WPKH and P2TR
+    /// key-path (BIP86). The value of the returned output is zero.
+    pub fn from_xpub(
+        xpub: &str,
+        index: u32,
+        address_type: RecipientKind,
+        network: bitcoin::Network,
+    ) -> Result<Proto::Output<'static>> {
+        let xpub = ExtendedPubKey::from_str(xpub)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_xpub))?;
+
+        // Extended keys only distinguish between mainnet and testnet.
+        let xpub_network = match network {
+            bitcoin::Network::Bitcoin => bitcoin::Network::Bitcoin,
+            _ => bitcoin::Network::Testnet,
+        };
+        if xpub.network != xpub_network {
+            return Err(Error::from(Proto::Error::Error_invalid_xpub));
+        }
+
+        // Hardened children can't be derived from a public key.
+        let child = ChildNumber::from_normal_idx(index)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_derivation_index))?;
+
+        let secp = secp256k1::Secp256k1::verification_only();
+        let pubkey = xpub
+            .derive_pub(&secp, &[child])
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_derivation_index))?
+            .public_key
+            .serialize()
+            .to_vec();
+
+        let variant = match address_type {
+            RecipientKind::P2pkh => ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.into()),
+            }),
+            RecipientKind::P2wpkh => ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.into()),
+            }),
+            RecipientKind::P2tr => ProtoOutputBuilder::p2tr_key_path(pubkey.into()),
+            _ => {
+                return Err(Error::from(
+                    Proto::Error::Error_unsupported_address_recipient,
+                ))
+            },
+        };
+
+        Ok(Proto::Output {
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant,
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Returns the human-readable (bech32m) P2TR address for the given
+    /// internal key and an optional merkle root of the script tree. The
+    /// internal key is tweaked accordingly; if no merkle root is provided,
+    /// the resulting address is a key-path only address (BIP86).
+    pub fn taproot_address(
+        internal_key: XOnlyPublicKey,
+        merkle_root: Option<TapNodeHash>,
+        network: bitcoin::Network,
+    ) -> Result<String> {
+        let secp = secp256k1::Secp256k1::new();
+        let address = Address::p2tr(&secp, internal_key, merkle_root, network);
+
+        Ok(address.to_string())
+    }
+
+    /// Computes the tweaked Taproot output key (and its parity) for the
+    /// given internal key and an optional merkle root of the script tree.
+    pub fn tweak_taproot_key(
+        internal_key: XOnlyPublicKey,
+        merkle_root: Option<TapNodeHash>,
+    ) -> (XOnlyPublicKey, Parity) {
+        let secp = secp256k1::Secp256k1::new();
+        let (tweaked, parity) = internal_key.tap_tweak(&secp, merkle_root);
+
+        (tweaked.to_inner(), parity)
+    }
+
+    /// Returns the canonical input sequence number that signals opt-in
+    /// replace-by-fee (BIP125) for the transaction.
+    pub fn rbf_sequence() -> u32 {
+        RBF_SEQUENCE
+    }
+
+    /// Whether the given input sequence number signals opt-in replace-by-fee
+    /// (BIP125), meaning any value below `0xfffffffe`.
+    pub fn is_rbf_signaled(sequence: u32) -> bool {
+        sequence < 0xfffffffe
+    }
+}
+
+// The taproot program (inscription script) is revealed as an element of the
+// reveal witness, which must not exceed the standard witness element limit.
+fn validate_program_size(inscription: &OrdinalsInscription) -> Result<()> {
+    if inscription.taproot_program().len() > MAX_STANDARD_TAPROOT_PROGRAM_SIZE {
+        return Err(Error::from(Proto::Error::Error_inscription_too_large));
+    }
+
+    Ok(())
+}
+
+// Returns the script that must be satisfied when spending the output, if it's
+// known: the custom scriptPubkey, the redeem script of a P2SH or P2WSH output
+// or the witness script of an HTLC.
+fn spending_script<'a>(recipient: &'a ProtoOutputRecipient) -> Option<Cow<'a, [u8]>> {
+    let script_or_hash = match recipient {
+        ProtoOutputRecipient::custom_script_pubkey(script) => {
+            return Some(Cow::Borrowed(script.as_ref()))
+        },
+        ProtoOutputRecipient::builder(builder) => match &builder.variant {
+            ProtoOutputBuilder::p2sh(script_or_hash)
+            | ProtoOutputBuilder::p2wsh(script_or_hash) => script_or_hash,
+            ProtoOutputBuilder::htlc(htlc) => {
+                return htlc_script(htlc)
+                    .ok()
+                    .map(|script| Cow::Owned(script.into_bytes()))
+            },
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    match &script_or_hash.variant {
+        ProtoRedeemScriptOrHash::redeem_script(script) => Some(Cow::Borrowed(script.as_ref())),
+        _ => None,
+    }
+}
+
+// Returns the `(min_locktime, min_sequence)` required by the
+// `<n> OP_CHECKLOCKTIMEVERIFY` and `<n> OP_CHECKSEQUENCEVERIFY` conditions of
+// the script. If there are multiple conditions, the highest value is used.
+fn timelock_hints(script: &Script) -> (u32, u32) {
+    use bitcoin::opcodes::all::{OP_CLTV, OP_CSV, OP_PUSHNUM_1, OP_PUSHNUM_16};
+
+    let (mut min_locktime, mut min_sequence) = (0, 0);
+    let mut last_num: Option<u32> = None;
+
+    for ins in script.instructions() {
+        let Ok(ins) = ins else {
+            break;
+        };
+
+        match ins {
+            Instruction::PushBytes(bytes) => {
+                last_num = bitcoin::script::read_scriptint(bytes.as_bytes())
+                    .ok()
+                    .and_then(|num| u32::try_from(num).ok());
+                continue;
+            },
+            Instruction::Op(op)
+                if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&op.to_u8()) =>
+            {
+                last_num = Some(u32::from(op.to_u8() - OP_PUSHNUM_1.to_u8() + 1));
+                continue;
+            },
+            Instruction::Op(OP_CLTV) => {
+                min_locktime = min_locktime.max(last_num.unwrap_or_default());
+            },
+            Instruction::Op(OP_CSV) => {
+                min_sequence = min_sequence.max(last_num.unwrap_or_default());
+            },
+            Instruction::Op(_) => {},
+        }
+
+        last_num = None;
+    }
+
+    (min_locktime, min_sequence)
+}
+
+// Creates the `OP_n <program>` scriptPubkey of a witness program. Version 0
+// programs must additionally be 20 (P2WPKH) or 32 (P2WSH) bytes long.
+fn witness_program_script(version: u32, program: &[u8]) -> Result<ScriptBuf> {
+    let valid_length = match version {
+        0 => program.len() == 20 || program.len() == 32,
+        1..=16 => (2..=40).contains(&program.len()),
+        _ => false,
+    };
+
+    if !valid_length {
+        return Err(Error::from(Proto::Error::Error_invalid_witness_program));
+    }
+
+    // `OP_0` or `OP_1` to `OP_16`.
+    let version_opcode = match version {
+        0 => 0x00,
+        _ => 0x50 + version as u8,
+    };
+
+    let mut script = vec![version_opcode, program.len() as u8];
+    script.extend_from_slice(program);
+
+    Ok(ScriptBuf::from_bytes(script))
+}
+
+// Creates the witness script of a hash time-locked contract. The recipient
+// spends with the preimage of the hash, the refund key after the timeout:
+//
+// `OP_IF OP_SHA256 <hash> OP_EQUALVERIFY <recipient> OP_ELSE <timeout> OP_CSV
+// OP_DROP <refund> OP_ENDIF OP_CHECKSIG`
+fn htlc_script(htlc: &Proto::mod_Output::OutputHtlc) -> Result<ScriptBuf> {
+    use bitcoin::opcodes::all::{
+        OP_CHECKSIG, OP_CSV, OP_DROP, OP_ELSE, OP_ENDIF, OP_EQUALVERIFY, OP_IF, OP_SHA256,
+    };
+
+    let hash: [u8; 32] = htlc
+        .hash
+        .as_ref()
+        .try_into()
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_htlc_hash))?;
+
+    let script = ScriptBuilder::new()
+        .push_opcode(OP_IF)
+        .push_opcode(OP_SHA256)
+        .push_slice(hash)
+        .push_opcode(OP_EQUALVERIFY)
+        .push_pubkey_checked(htlc.recipient_pubkey.as_ref())?
+        .push_opcode(OP_ELSE)
+        .push_int_checked(i64::from(htlc.timeout))?
+        .push_opcode(OP_CSV)
+        .push_opcode(OP_DROP)
+        .push_pubkey_checked(htlc.refund_pubkey.as_ref())?
+        .push_opcode(OP_ENDIF)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+
+    Ok(script)
+}
+
+// Returns the kind of script the given builder is expected to produce, if any.
+fn expected_recipient_kind(recipient: &ProtoOutputRecipient) -> Option<RecipientKind> {
+    let ProtoOutputRecipient::builder(builder) = recipient else {
+        return None;
+    };
+
+    let kind = match &builder.variant {
+        ProtoOutputBuilder::p2sh(_) => RecipientKind::P2sh,
+        ProtoOutputBuilder::p2pkh(_) => RecipientKind::P2pkh,
+        ProtoOutputBuilder::p2wsh(_) => RecipientKind::P2wsh,
+        ProtoOutputBuilder::p2wpkh(_) => RecipientKind::P2wpkh,
+        ProtoOutputBuilder::p2tr_key_path(_)
+        | ProtoOutputBuilder::p2tr_script_path(_)
+        | ProtoOutputBuilder::p2tr_dangerous_assume_tweaked(_)
+        | ProtoOutputBuilder::ordinal_inscribe(_)
+        | ProtoOutputBuilder::brc20_inscribe(_) => RecipientKind::P2tr,
+        ProtoOutputBuilder::op_return(_) => RecipientKind::OpReturn,
+        ProtoOutputBuilder::silent_payment(_) => RecipientKind::P2tr,
+        ProtoOutputBuilder::htlc(_) => RecipientKind::P2wsh,
+        // Depends on the version and the program.
+        ProtoOutputBuilder::witness_program(_) => return None,
+        ProtoOutputBuilder::None => return None,
+    };
+
+    Some(kind)
+}
+
+// Whether the opcode is disabled by consensus (CVE-2010-5137), which makes any
+// script that contains it unspendable.
+fn is_disabled_opcode(op: bitcoin::opcodes::All) -> bool {
+    use bitcoin::opcodes::all::*;
+
+    [
+        OP_CAT, OP_SUBSTR, OP_LEFT, OP_RIGHT, OP_INVERT, OP_AND, OP_OR, OP_XOR, OP_2MUL, OP_2DIV,
+        OP_MUL, OP_DIV, OP_MOD, OP_LSHIFT, OP_RSHIFT,
+    ]
+    .contains(&op)
+}
+
+// Returns the `(required, total)` number of signatures if the script is a
+// bare multisig script: `OP_m <pubkey>... OP_n OP_CHECKMULTISIG`.
+fn multisig_params(script: &Script) -> Option<(u8, u8)> {
+    use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_1, OP_PUSHNUM_16};
+
+    // Convenience function: reads the `OP_1` to `OP_16` number.
+    let push_num = |ins: &Instruction| match ins {
+        Instruction::Op(op)
+            if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&op.to_u8()) =>
+        {
+            Some(op.to_u8() - OP_PUSHNUM_1.to_u8() + 1)
+        },
+        _ => None,
+    };
+
+    let instructions = script
+        .instructions()
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .ok()?;
+
+    let (first, rest) = instructions.split_first()?;
+    let (last, rest) = rest.split_last()?;
+    let (total, pubkeys) = rest.split_last()?;
+
+    if *last != Instruction::Op(OP_CHECKMULTISIG) {
+        return None;
+    }
+
+    let required = push_num(first)?;
+    let total = push_num(total)?;
+
+    let valid_pubkeys = pubkeys.iter().all(|ins| match ins {
+        Instruction::PushBytes(bytes) => bitcoin::PublicKey::from_slice(bytes.as_bytes()).is_ok(),
+        _ => false,
+    });
+
+    if !valid_pubkeys || pubkeys.len() != total as usize || required > total {
+        return None;
+    }
+
+    Some((required, total))
 }
 
 // Convenience helper function.
@@ -202,8 +992,17 @@ fn redeem_script_or_hash(
     script_or_hash: &Proto::mod_Output::OutputRedeemScriptOrHash,
 ) -> Result<ScriptHash> {
     let pubkey_hash = match &script_or_hash.variant {
-        ProtoRedeemScriptOrHash::hash(hash) => ScriptHash::from_slice(hash.as_ref())
-            .map_err(|_| Error::from(Proto::Error::Error_invalid_redeem_script))?,
+        ProtoRedeemScriptOrHash::hash(hash) => {
+            // P2SH uses 20-byte script hashes (HASH160).
+            if hash.len() != 20 {
+                return Err(Error::from(
+                    Proto::Error::Error_invalid_redeem_script_hash_length,
+                ));
+            }
+
+            ScriptHash::from_slice(hash.as_ref())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_redeem_script))?
+        },
         ProtoRedeemScriptOrHash::redeem_script(script) => {
             ScriptBuf::from_bytes(script.to_vec()).script_hash()
         },
@@ -220,10 +1019,21 @@ fn witness_redeem_script_or_hash(
     script_or_hash: &Proto::mod_Output::OutputRedeemScriptOrHash,
 ) -> Result<WScriptHash> {
     let pubkey_hash = match &script_or_hash.variant {
-        ProtoRedeemScriptOrHash::hash(hash) => WScriptHash::from_slice(hash)
-            .map_err(|_| Error::from(Proto::Error::Error_invalid_witness_redeem_script_hash))?,
+        ProtoRedeemScriptOrHash::hash(hash) => {
+            // P2WSH uses 32-byte script hashes (SHA256).
+            if hash.len() != 32 {
+                return Err(Error::from(
+                    Proto::Error::Error_invalid_witness_redeem_script_hash_length,
+                ));
+            }
+
+            WScriptHash::from_slice(hash)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_witness_redeem_script_hash))?
+        },
         ProtoRedeemScriptOrHash::redeem_script(script) => {
-            ScriptBuf::from_bytes(script.to_vec()).wscript_hash()
+            let script = Script::from_bytes(script.as_ref());
+            validate_witness_script(script)?;
+            script.wscript_hash()
         },
         ProtoRedeemScriptOrHash::None => {
             return Err(Error::from(Proto::Error::Error_missing_recipient))
@@ -233,11 +1043,56 @@ fn witness_redeem_script_or_hash(
     Ok(pubkey_hash)
 }
 
+// Creates an `OP_RETURN <push1> <push2> ...` script. The size of the entire
+// script must not exceed the standardness limit, which allows for up to 80
+// bytes of data in a single push.
+fn op_return_script(data: &[Cow<'_, [u8]>]) -> Result<ScriptBuf> {
+    use bitcoin::opcodes::all::OP_RETURN;
+
+    let mut builder = ScriptBuf::builder().push_opcode(OP_RETURN);
+    for push in data {
+        let push = PushBytesBuf::try_from(push.to_vec())
+            .map_err(|_| Error::from(Proto::Error::Error_op_return_too_large))?;
+
+        builder = builder.push_slice(push);
+    }
+
+    let script = builder.into_script();
+    if script.len() > MAX_STANDARD_OP_RETURN_SIZE {
+        return Err(Error::from(Proto::Error::Error_op_return_too_large));
+    }
+
+    Ok(script)
+}
+
+// Checks that the witness script is not empty, can be parsed and does not
+// exceed the standardness limit.
+fn validate_witness_script(script: &Script) -> Result<()> {
+    let invalid = || Error::from(Proto::Error::Error_invalid_witness_redeem_script);
+
+    if script.is_empty() || script.len() > MAX_STANDARD_P2WSH_SCRIPT_SIZE {
+        return Err(invalid());
+    }
+
+    if script.instructions().any(|ins| ins.is_err()) {
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
 // Convenience helper function.
 fn pubkey_hash_from_proto(pubkey_or_hash: &Proto::ToPublicKeyOrHash) -> Result<PubkeyHash> {
     let pubkey_hash = match &pubkey_or_hash.to_address {
-        ProtoPubkeyOrHash::hash(hash) => PubkeyHash::from_slice(hash.as_ref())
-            .map_err(|_| Error::from(Proto::Error::Error_invalid_pubkey_hash))?,
+        ProtoPubkeyOrHash::hash(hash) => {
+            // P2PKH uses 20-byte public key hashes (HASH160).
+            if hash.len() != 20 {
+                return Err(Error::from(Proto::Error::Error_invalid_pubkey_hash_length));
+            }
+
+            PubkeyHash::from_slice(hash.as_ref())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_pubkey_hash))?
+        },
         ProtoPubkeyOrHash::pubkey(pubkey) => {
             bitcoin::PublicKey::from_slice(pubkey.as_ref())?.pubkey_hash()
         },
@@ -252,8 +1107,18 @@ fn witness_pubkey_hash_from_proto(
     pubkey_or_hash: &Proto::ToPublicKeyOrHash,
 ) -> Result<WPubkeyHash> {
     let wpubkey_hash = match &pubkey_or_hash.to_address {
-        ProtoPubkeyOrHash::hash(hash) => WPubkeyHash::from_slice(hash.as_ref())
-            .map_err(|_| Error::from(Proto::Error::Error_invalid_witness_pubkey_hash))?,
+        ProtoPubkeyOrHash::hash(hash) => {
+            // P2WPKH uses 20-byte public key hashes (HASH160), not 32-byte
+            // script hashes.
+            if hash.len() != 20 {
+                return Err(Error::from(
+                    Proto::Error::Error_invalid_witness_pubkey_hash_length,
+                ));
+            }
+
+            WPubkeyHash::from_slice(hash.as_ref())
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_witness_pubkey_hash))?
+        },
         ProtoPubkeyOrHash::pubkey(pubkey) => bitcoin::PublicKey::from_slice(pubkey.as_ref())?
             .wpubkey_hash()
             .ok_or_else(|| Error::from(Proto::Error::Error_invalid_witness_pubkey_hash))?,
@@ -263,15 +1128,47 @@ fn witness_pubkey_hash_from_proto(
     Ok(wpubkey_hash)
 }
 
-// Derives the P2* output from the given address.
+// Maps the error of parsing an address, distinguishing invalid checksums and
+// addresses of a different network from otherwise malformed addresses.
+fn address_error(err: bitcoin::address::Error) -> Error {
+    use bitcoin::address::Error as AddressError;
+
+    match err {
+        AddressError::Base58(bitcoin::base58::Error::BadChecksum(_, _))
+        | AddressError::Bech32(bitcoin::bech32::Error::InvalidChecksum) => {
+            Error::from(Proto::Error::Error_invalid_address_checksum)
+        },
+        AddressError::NetworkValidation { .. } => {
+            Error::from(Proto::Error::Error_address_wrong_network)
+        },
+        _ => Error::from(Proto::Error::Error_bad_address_recipient),
+    }
+}
+
+// Derives the P2* output from the given mainnet address.
 fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>> {
+    output_from_address_for_network(value, addr, BitcoinNetwork::Bitcoin)
+}
+
+// Derives the P2* output from the given address, which must belong to the
+// given network.
+fn output_from_address_for_network(
+    value: u64,
+    addr: &str,
+    network: BitcoinNetwork,
+) -> Result<Proto::Output<'static>> {
+    // Reject oversized or non-ASCII input early, without attempting to parse.
+    if addr.len() > MAX_ADDRESS_LENGTH || !addr.is_ascii() {
+        return Err(Error::from(Proto::Error::Error_bad_address_recipient));
+    }
+
     let string = String::from_utf8(addr.to_vec())
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
 
     let addr = Address::from_str(&string)
-        .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?
-        .require_network(bitcoin::Network::Bitcoin)
-        .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
+        .map_err(address_error)?
+        .require_network(network.address_network())
+        .map_err(address_error)?;
 
     let proto = match addr.payload {
         // Identified a "PubkeyHash" address (i.e. P2PKH).
@@ -282,6 +1179,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                     to_address: ProtoPubkeyOrHash::hash(pubkey_hash.to_vec().into()),
                 }),
             }),
+            ..Default::default()
         },
         // Identified a witness program (i.e. Segwit or Taproot).
         Payload::WitnessProgram(progam) => {
@@ -304,6 +1202,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                                     }),
                                 },
                             ),
+                            ..Default::default()
                         });
                     }
 
@@ -323,6 +1222,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                                     ),
                                 },
                             ),
+                            ..Default::default()
                         });
                     }
 
@@ -344,6 +1244,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                                 ),
                             },
                         ),
+                        ..Default::default()
                     }
                 },
                 _ => {
@@ -362,6 +1263,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                     ),
                 }),
             }),
+            ..Default::default()
         },
         _ => {
             return Err(Error::from(
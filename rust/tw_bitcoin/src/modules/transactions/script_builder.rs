@@ -0,0 +1,121 @@
+use crate::{Error, Result};
+use bitcoin::opcodes::All as Opcode;
+use bitcoin::script::{self, PushBytes};
+use bitcoin::ScriptBuf;
+use tw_proto::BitcoinV2::Proto;
+
+/// The maximum magnitude of a script number consumed by arithmetic opcodes,
+/// which are limited to four bytes (`CScriptNum`).
+const MAX_SCRIPT_NUM: i64 = i32::MAX as i64;
+
+/// Assembles scripts opcode-by-opcode, like [`script::Builder`], but
+/// validates the pushed integers and public keys.
+pub(crate) struct ScriptBuilder(script::Builder);
+
+impl ScriptBuilder {
+    pub fn new() -> Self {
+        ScriptBuilder(script::Builder::new())
+    }
+    pub fn push_opcode(self, opcode: Opcode) -> Self {
+        ScriptBuilder(self.0.push_opcode(opcode))
+    }
+    pub fn push_slice<T: AsRef<PushBytes>>(self, data: T) -> Self {
+        ScriptBuilder(self.0.push_slice(data))
+    }
+    /// Pushes the integer with minimal encoding. The integer must fit into a
+    /// four byte script number.
+    pub fn push_int_checked(self, int: i64) -> Result<Self> {
+        if !(-MAX_SCRIPT_NUM..=MAX_SCRIPT_NUM).contains(&int) {
+            return Err(Error::from(Proto::Error::Error_invalid_script_number));
+        }
+
+        Ok(ScriptBuilder(self.0.push_int(int)))
+    }
+    /// Pushes the public key, which must be a valid compressed (33 bytes)
+    /// public key.
+    pub fn push_pubkey_checked(self, pubkey: &[u8]) -> Result<Self> {
+        let pubkey = bitcoin::PublicKey::from_slice(pubkey)?;
+        if !pubkey.compressed {
+            return Err(Error::from(Proto::Error::Error_uncompressed_public_key));
+        }
+
+        Ok(ScriptBuilder(self.0.push_key(&pubkey)))
+    }
+    pub fn into_script(self) -> ScriptBuf {
+        self.0.into_script()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::opcodes::all::OP_CHECKSIG;
+
+    const PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+    const UNCOMPRESSED_PUBKEY: &str = "048d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f2c2ef1572b87738c732c66d4a3770d41bb1bda428ce96b0c9829243a0a6a96ae";
+
+    fn error_of<T>(res: Result<T>) -> Proto::Error {
+        match res {
+            Ok(_) => panic!("expected an error"),
+            Err(err) => Proto::Error::from(err),
+        }
+    }
+
+    #[test]
+    fn script_builder_int_pushes() {
+        // Small integers are pushed as `OP_0`, `OP_1NEGATE` and `OP_1`-`OP_16`.
+        let script = ScriptBuilder::new()
+            .push_int_checked(0)
+            .unwrap()
+            .push_int_checked(-1)
+            .unwrap()
+            .push_int_checked(16)
+            .unwrap()
+            .into_script();
+        assert_eq!(script.as_bytes(), &[0x00, 0x4f, 0x60]);
+
+        // Larger integers are pushed as minimally encoded script numbers.
+        let script = ScriptBuilder::new()
+            .push_int_checked(144)
+            .unwrap()
+            .into_script();
+        assert_eq!(script.as_bytes(), &[0x02, 0x90, 0x00]);
+
+        let script = ScriptBuilder::new()
+            .push_int_checked(MAX_SCRIPT_NUM)
+            .unwrap()
+            .into_script();
+        assert_eq!(script.as_bytes(), &[0x04, 0xff, 0xff, 0xff, 0x7f]);
+
+        // Integers exceeding four bytes are rejected.
+        for int in [MAX_SCRIPT_NUM + 1, -MAX_SCRIPT_NUM - 1, i64::MAX] {
+            let res = ScriptBuilder::new().push_int_checked(int);
+            assert_eq!(error_of(res), Proto::Error::Error_invalid_script_number);
+        }
+    }
+
+    #[test]
+    fn script_builder_pubkey_pushes() {
+        let pubkey = tw_encoding::hex::decode(PUBKEY).unwrap();
+
+        let script = ScriptBuilder::new()
+            .push_pubkey_checked(&pubkey)
+            .unwrap()
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        let mut expected = vec![0x21];
+        expected.extend(&pubkey);
+        expected.push(0xac);
+        assert_eq!(script.as_bytes(), expected.as_slice());
+
+        // Not a valid public key.
+        let res = ScriptBuilder::new().push_pubkey_checked(&pubkey[1..]);
+        assert_eq!(error_of(res), Proto::Error::Error_invalid_public_key);
+
+        // Uncompressed public keys are rejected.
+        let uncompressed = tw_encoding::hex::decode(UNCOMPRESSED_PUBKEY).unwrap();
+        let res = ScriptBuilder::new().push_pubkey_checked(&uncompressed);
+        assert_eq!(error_of(res), Proto::Error::Error_uncompressed_public_key);
+    }
+}
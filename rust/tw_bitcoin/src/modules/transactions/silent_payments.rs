@@ -0,0 +1,147 @@
+use crate::{Error, Result};
+use bitcoin::key::TweakedPublicKey;
+use bitcoin::ScriptBuf;
+use secp256k1::hashes::{sha256, Hash, HashEngine};
+use secp256k1::{PublicKey, Scalar, SecretKey, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
+
+const INPUTS_TAG: &[u8] = b"BIP0352/Inputs";
+const SHARED_SECRET_TAG: &[u8] = b"BIP0352/SharedSecret";
+
+/// Derives the P2TR _scriptPubkey_ of a [silent payment] output.
+///
+/// [silent payment]: https://github.com/bitcoin/bips/blob/master/bip-0352.mediawiki
+pub(crate) fn silent_payment_script(
+    payment: &Proto::mod_Output::OutputSilentPayment,
+) -> Result<ScriptBuf> {
+    let output_key = silent_payment_output_key(payment)?;
+    Ok(ScriptBuf::new_v1_p2tr_tweaked(
+        TweakedPublicKey::dangerous_assume_tweaked(output_key),
+    ))
+}
+
+/// Derives the (x-only) output key of a silent payment:
+/// `B_spend + hash(input_hash * a * B_scan || k) * G`.
+fn silent_payment_output_key(
+    payment: &Proto::mod_Output::OutputSilentPayment,
+) -> Result<XOnlyPublicKey> {
+    let secp = secp256k1::Secp256k1::new();
+
+    let scan_key = PublicKey::from_slice(payment.recipient_scan_key.as_ref())
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+    let spend_key = PublicKey::from_slice(payment.recipient_spend_key.as_ref())
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+    if payment.input_pubkeys.is_empty()
+        || payment.input_pubkeys.len() != payment.input_private_keys.len()
+    {
+        return Err(Error::from(Proto::Error::Error_invalid_silent_payment));
+    }
+
+    // Sum up the private keys of the inputs, each must match its public key.
+    let mut input_key_sum: Option<SecretKey> = None;
+    for (pubkey, private_key) in payment
+        .input_pubkeys
+        .iter()
+        .zip(payment.input_private_keys.iter())
+    {
+        let mut private_key = SecretKey::from_slice(private_key.as_ref())
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+        let (xonly, parity) = private_key.public_key(&secp).x_only_public_key();
+
+        let matches = match pubkey.len() {
+            // Taproot inputs commit to the even key, hence the private key
+            // must be negated if the public key has an odd Y coordinate.
+            32 => {
+                if parity == secp256k1::Parity::Odd {
+                    private_key = private_key.negate();
+                }
+
+                XOnlyPublicKey::from_slice(pubkey.as_ref()).map(|key| key == xonly)
+            },
+            _ => PublicKey::from_slice(pubkey.as_ref())
+                .map(|key| key == private_key.public_key(&secp)),
+        }
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+        if !matches {
+            return Err(Error::from(Proto::Error::Error_invalid_silent_payment));
+        }
+
+        input_key_sum = Some(match input_key_sum {
+            None => private_key,
+            // Fails if the keys sum up to zero.
+            Some(sum) => sum
+                .add_tweak(&Scalar::from(private_key))
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?,
+        });
+    }
+
+    let input_key_sum = input_key_sum.expect("at least one input key");
+    let input_pubkey_sum = input_key_sum.public_key(&secp);
+
+    // The outpoints must be unique, the lexicographically smallest one is
+    // committed to.
+    let mut outpoints = payment
+        .outpoints
+        .iter()
+        .map(|outpoint| {
+            if outpoint.txid.len() != 32 {
+                return Err(Error::from(Proto::Error::Error_invalid_silent_payment));
+            }
+
+            let mut serialized = outpoint.txid.to_vec();
+            serialized.extend_from_slice(&outpoint.vout.to_le_bytes());
+            Ok(serialized)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    outpoints.sort();
+    let len_before_dedup = outpoints.len();
+    outpoints.dedup();
+    if outpoints.len() != len_before_dedup {
+        return Err(Error::from(Proto::Error::Error_invalid_silent_payment));
+    }
+
+    let smallest_outpoint = outpoints
+        .first()
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+    let input_hash = tagged_hash(
+        INPUTS_TAG,
+        &[smallest_outpoint.as_slice(), &input_pubkey_sum.serialize()],
+    )?;
+
+    // The ECDH shared secret: `input_hash * a * B_scan`.
+    let shared_secret = scan_key
+        .mul_tweak(&secp, &input_hash)
+        .and_then(|key| key.mul_tweak(&secp, &Scalar::from(input_key_sum)))
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+    let tweak = tagged_hash(
+        SHARED_SECRET_TAG,
+        &[&shared_secret.serialize(), &payment.k.to_be_bytes()],
+    )?;
+
+    let output_key = spend_key
+        .add_exp_tweak(&secp, &tweak)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+    Ok(output_key.x_only_public_key().0)
+}
+
+// Computes the BIP340 tagged hash of the given data and interprets it as a
+// scalar. Fails if the hash is not below the curve order.
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> Result<Scalar> {
+    let tag_hash = sha256::Hash::hash(tag);
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag_hash.as_ref());
+    engine.input(tag_hash.as_ref());
+    for item in data {
+        engine.input(item);
+    }
+
+    Scalar::from_be_bytes(sha256::Hash::from_engine(engine).to_byte_array())
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))
+}
@@ -0,0 +1,63 @@
+use crate::{Error, Result};
+use bitcoin::hashes::Hash;
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapNodeHash, TAPROOT_CONTROL_BASE_SIZE};
+use bitcoin::{Script, ScriptBuf};
+use secp256k1::XOnlyPublicKey;
+use tw_proto::BitcoinV2::Proto;
+
+/// Everything required to spend a built P2TR output via the script-path,
+/// bundled into a single object: the leaf script, its control block, the
+/// leaf version and the Merkle root of the script tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendInfo {
+    pub script: ScriptBuf,
+    pub control_block: ControlBlock,
+    pub leaf_version: LeafVersion,
+    pub merkle_root: TapNodeHash,
+}
+
+impl SpendInfo {
+    /// Creates the spend info from a built output, i.e. from its
+    /// `taproot_payload` and `control_block`. The control block must commit
+    /// to the script and the output key of the _scriptPubkey_.
+    pub fn from_txout(txout: &Proto::mod_PreSigningOutput::TxOut<'_>) -> Result<SpendInfo> {
+        if txout.taproot_payload.is_empty() || txout.control_block.is_empty() {
+            return Err(Error::from(Proto::Error::Error_missing_taproot_spend_info));
+        }
+
+        let script_pubkey = Script::from_bytes(txout.script_pubkey.as_ref());
+        if !script_pubkey.is_v1_p2tr() {
+            return Err(Error::from(Proto::Error::Error_missing_taproot_spend_info));
+        }
+
+        // `OP_1 <32-byte output key>`
+        let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..])
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_taproot_tweaked_pubkey))?;
+
+        let script = ScriptBuf::from_bytes(txout.taproot_payload.to_vec());
+        let control_block = ControlBlock::decode(txout.control_block.as_ref())
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+
+        let secp = secp256k1::Secp256k1::verification_only();
+        if !control_block.verify_taproot_commitment(&secp, output_key, &script) {
+            return Err(Error::from(Proto::Error::Error_invalid_control_block));
+        }
+
+        // Hash the leaf up the Merkle branch of the control block.
+        let leaf_version = control_block.leaf_version;
+        let merkle_root = txout.control_block[TAPROOT_CONTROL_BASE_SIZE..]
+            .chunks(32)
+            .map(|node| TapNodeHash::from_slice(node).expect("node hashes are 32 bytes"))
+            .fold(
+                TapNodeHash::from_script(&script, leaf_version),
+                TapNodeHash::from_node_hashes,
+            );
+
+        Ok(SpendInfo {
+            script,
+            control_block,
+            leaf_version,
+            merkle_root,
+        })
+    }
+}
@@ -0,0 +1,17 @@
+/// The standardness policy applied when validating _scriptPubkeys_.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StandardnessPolicy {
+    /// No standardness checks; hand-crafted scripts are accepted as is.
+    #[default]
+    Disabled,
+    /// Rejects scripts that exceed the maximum script size or that contain
+    /// disabled opcodes, since such outputs can never be spent.
+    Strict,
+}
+
+impl StandardnessPolicy {
+    /// Whether the standardness checks are enabled.
+    pub fn is_strict(&self) -> bool {
+        matches!(self, StandardnessPolicy::Strict)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{Error, Result};
+use bitcoin::consensus::encode::deserialize_partial;
+use bitcoin::taproot::{LeafVersion, TapNodeHash, TAPROOT_CONTROL_MAX_NODE_COUNT};
+use bitcoin::ScriptBuf;
+use tw_proto::BitcoinV2::Proto;
+
+/// A leaf of a [`TapTree`], at the given depth of the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapTreeLeaf {
+    pub depth: u8,
+    pub leaf_version: LeafVersion,
+    pub script: ScriptBuf,
+}
+
+/// A complete Taproot script tree, described by its leaves in depth-first
+/// order. This allows wallets to persist the full tree of a script-path
+/// output for later spending, rather than just a single control block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapTree {
+    leaves: Vec<TapTreeLeaf>,
+    merkle_root: TapNodeHash,
+}
+
+impl TapTree {
+    /// Creates the tree from its leaves in depth-first order. The leaves must
+    /// describe a complete binary tree, e.g. a single leaf at depth 0 or two
+    /// leaves at depth 1.
+    pub fn new(leaves: Vec<TapTreeLeaf>) -> Result<TapTree> {
+        let merkle_root = merkle_root(&leaves)?;
+
+        Ok(TapTree {
+            leaves,
+            merkle_root,
+        })
+    }
+    pub fn leaves(&self) -> &[TapTreeLeaf] {
+        &self.leaves
+    }
+    pub fn merkle_root(&self) -> TapNodeHash {
+        self.merkle_root
+    }
+    /// Serializes the tree in the format of the BIP371 `PSBT_OUT_TAP_TREE`
+    /// field: `{<depth> <leaf version> <compact size script length> <script>}*`
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = vec![];
+
+        for leaf in &self.leaves {
+            serialized.push(leaf.depth);
+            serialized.push(leaf.leaf_version.to_consensus());
+            // The script is encoded with a compact size length prefix.
+            serialized.extend(bitcoin::consensus::serialize(&leaf.script));
+        }
+
+        serialized
+    }
+    /// Deserializes a tree written by [`TapTree::serialize`].
+    pub fn deserialize(mut bytes: &[u8]) -> Result<TapTree> {
+        let mut leaves = vec![];
+
+        while !bytes.is_empty() {
+            let [depth, leaf_version, rest @ ..] = bytes else {
+                return Err(Error::from(Proto::Error::Error_invalid_tap_tree));
+            };
+
+            let leaf_version = LeafVersion::from_consensus(*leaf_version)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_leaf_version))?;
+
+            let (script, consumed) = deserialize_partial::<ScriptBuf>(rest)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_tap_tree))?;
+
+            leaves.push(TapTreeLeaf {
+                depth: *depth,
+                leaf_version,
+                script,
+            });
+
+            bytes = &rest[consumed..];
+        }
+
+        TapTree::new(leaves)
+    }
+}
+
+// Computes the Merkle root of the leaves in depth-first order. Sibling nodes
+// are combined as soon as both are known, which fails if the leaves don't
+// form a complete tree.
+fn merkle_root(leaves: &[TapTreeLeaf]) -> Result<TapNodeHash> {
+    let invalid = || Error::from(Proto::Error::Error_invalid_tap_tree);
+
+    // The pending (left) nodes, with their depth.
+    let mut stack: Vec<(u8, TapNodeHash)> = vec![];
+
+    for leaf in leaves {
+        // The tree is already complete.
+        if matches!(stack.as_slice(), [(0, _)]) {
+            return Err(invalid());
+        }
+
+        if leaf.depth as usize > TAPROOT_CONTROL_MAX_NODE_COUNT {
+            return Err(invalid());
+        }
+
+        let mut depth = leaf.depth;
+        let mut hash = TapNodeHash::from_script(&leaf.script, leaf.leaf_version);
+
+        while let Some(&(left_depth, left)) = stack.last() {
+            if left_depth != depth {
+                break;
+            }
+
+            stack.pop();
+            hash = TapNodeHash::from_node_hashes(left, hash);
+            depth -= 1;
+        }
+
+        // The left sibling's subtree is incomplete.
+        if stack
+            .last()
+            .map_or(false, |(left_depth, _)| *left_depth > depth)
+        {
+            return Err(invalid());
+        }
+
+        stack.push((depth, hash));
+    }
+
+    match stack.as_slice() {
+        [(0, root)] => Ok(*root),
+        _ => Err(invalid()),
+    }
+}
@@ -1,7 +1,11 @@
 mod common;
 
+use bitcoin::taproot::{ControlBlock, TapLeafHash, TapNodeHash};
+use bitcoin::PublicKey;
 use common::hex;
+use secp256k1::{KeyPair, Message, Secp256k1};
 use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::transactions::{BRC20TransferInscription, Brc20Ticker, OutputBuilder};
 use tw_bitcoin::BitcoinEntry;
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
@@ -42,6 +46,7 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
                 },
             ),
         }),
+        ..Default::default()
     };
 
     // Change/return transaction.
@@ -52,6 +57,7 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -106,6 +112,7 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -135,3 +142,137 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
+
+#[test]
+fn brc20_ticker_normalized() {
+    let ticker = Brc20Ticker::new_normalized("Ordi".to_string()).unwrap();
+    assert_eq!(serde_json::to_string(&ticker).unwrap(), r#""ordi""#);
+
+    // The raw constructor keeps the exact casing.
+    let ticker = Brc20Ticker::new("Ordi".to_string()).unwrap();
+    assert_eq!(serde_json::to_string(&ticker).unwrap(), r#""Ordi""#);
+
+    // The length is validated on the original bytes; "ORȺ" has four bytes,
+    // while its lowercase form "orⱥ" has five.
+    let ticker = Brc20Ticker::new_normalized("ORȺ".to_string()).unwrap();
+    assert_eq!(serde_json::to_string(&ticker).unwrap(), r#""orⱥ""#);
+
+    let err = Brc20Ticker::new_normalized("Ordinals".to_string()).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_brc20_ticker
+    );
+}
+
+#[test]
+fn brc20_reveal_witness() {
+    let secp = Secp256k1::new();
+
+    let alice_private_key = hex("e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129");
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+
+    let ticker = Brc20Ticker::new("oadf".to_string()).unwrap();
+    let transfer = BRC20TransferInscription::new(alice_pubkey, ticker, "20".to_string()).unwrap();
+    let inscription = transfer.inscription();
+
+    // Any (valid) Schnorr signature will do here.
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let sig = bitcoin::taproot::Signature {
+        sig: secp.sign_schnorr_no_aux_rand(&Message::from_slice(&[1; 32]).unwrap(), &keypair),
+        hash_ty: bitcoin::sighash::TapSighashType::Default,
+    };
+
+    let witness = transfer.reveal_witness(&sig);
+    assert_eq!(witness.len(), 3);
+    assert_eq!(witness.nth(0).unwrap(), sig.to_vec());
+    assert_eq!(
+        witness.nth(1).unwrap(),
+        inscription.taproot_program().as_bytes()
+    );
+
+    // The revealed script and the control block commit to the merkle root,
+    // and therefore to the output key, of the inscription.
+    let control_block = ControlBlock::decode(witness.nth(2).unwrap()).unwrap();
+    let script = bitcoin::Script::from_bytes(witness.nth(1).unwrap());
+
+    let leaf_hash = TapLeafHash::from_script(script, control_block.leaf_version);
+    assert_eq!(
+        Some(TapNodeHash::from(leaf_hash)),
+        inscription.spend_info().merkle_root()
+    );
+
+    let output_key = inscription.spend_info().output_key();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key.to_inner(), script));
+}
+
+#[test]
+fn brc20_inscribe_to_key_formats() {
+    let compressed = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let xonly = compressed[1..].to_vec();
+    let uncompressed = hex("040f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb22c780bb4a89a9e37bae450b2609117bd9a19a8adb4b6a87fdd2e7d0fd7ae50d");
+
+    let output = |inscribe_to: &[u8]| Proto::Output {
+        value: 7_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::brc20_inscribe(
+                Proto::mod_Output::OutputBrc20Inscription {
+                    inscribe_to: inscribe_to.to_vec().into(),
+                    ticker: "oadf".into(),
+                    transfer_amount: "20".into(),
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let from_compressed = OutputBuilder::utxo_from_proto(&output(&compressed)).unwrap();
+
+    // The x-only key results in the same output.
+    let from_xonly = OutputBuilder::utxo_from_proto(&output(&xonly)).unwrap();
+    assert_eq!(from_xonly.script_pubkey, from_compressed.script_pubkey);
+    assert_eq!(from_xonly.taproot_payload, from_compressed.taproot_payload);
+
+    let err = OutputBuilder::utxo_from_proto(&output(&uncompressed)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_uncompressed_public_key
+    );
+
+    let err = OutputBuilder::utxo_from_proto(&output(&compressed[..20])).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+}
+
+#[test]
+fn brc20_inscription_too_large() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+
+    let output = |transfer_amount: String| Proto::Output {
+        value: 7_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::brc20_inscribe(
+                Proto::mod_Output::OutputBrc20Inscription {
+                    inscribe_to: alice_pubkey.as_slice().into(),
+                    ticker: "oadf".into(),
+                    transfer_amount: transfer_amount.into(),
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    // The JSON payload (53 bytes plus the amount) results in a 399,517 byte
+    // taproot program, the largest standard witness element.
+    let utxo = OutputBuilder::utxo_from_proto(&output("1".repeat(397_137))).unwrap();
+    assert_eq!(utxo.taproot_payload.len(), 399_517);
+
+    // One more byte exceeds the standard witness element limit.
+    let err = OutputBuilder::utxo_from_proto(&output("1".repeat(397_138))).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_inscription_too_large
+    );
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "elements")]
+
+mod common;
+
+use bitcoin::{PubkeyHash, ScriptBuf, ScriptHash};
+use common::hex;
+use secp256k1::hashes::Hash;
+use std::str::FromStr;
+use tw_bitcoin::modules::transactions::{ConfidentialAddress, ElementsParams, OutputBuilder};
+use tw_proto::BitcoinV2::Proto;
+
+const BLINDING_PUBKEY: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const HASH: &str = "60cda7b50f14c152d7401c28ae773c698db92373";
+
+#[test]
+fn elements_confidential_p2pkh_address() {
+    let address = ConfidentialAddress::from_str(
+        "VTpwKsrwasw7VnNf4GHMmcjNY3MR2Q81GaxDv7EyhVS8rzj3oYCWWmDdUxrzh3AxVB4xK9ycAM6CDyut",
+    )
+    .unwrap();
+
+    let expected = ScriptBuf::new_p2pkh(&PubkeyHash::from_slice(&hex(HASH)).unwrap());
+
+    assert_eq!(address.params, ElementsParams::LIQUID);
+    assert_eq!(
+        address.blinding_pubkey.serialize().to_vec(),
+        hex(BLINDING_PUBKEY)
+    );
+    assert_eq!(address.script_pubkey, expected);
+}
+
+#[test]
+fn elements_confidential_p2sh_output() {
+    let (utxo, blinding_pubkey) = OutputBuilder::elements_confidential(
+        1_000,
+        "VJL8r24A8tovW2f1hmFsHNXPTqBU1rp77hFp7wwj6pkkEboB91ebNhVf6UpG9G5rbT3xs5Z8Nysw5h8r",
+    )
+    .unwrap();
+
+    let expected = ScriptBuf::new_p2sh(&ScriptHash::from_slice(&hex(HASH)).unwrap());
+
+    assert_eq!(utxo.value, 1_000);
+    assert_eq!(utxo.script_pubkey, expected.as_bytes());
+    assert_eq!(blinding_pubkey.serialize().to_vec(), hex(BLINDING_PUBKEY));
+}
+
+#[test]
+fn elements_unconfidential_address() {
+    // A regular Bitcoin address does not carry a blinding key.
+    let err = ConfidentialAddress::from_str("19prEapJCTF3zAS2ofreXyQhcnDscuXxbd").unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_bad_address_recipient
+    );
+}
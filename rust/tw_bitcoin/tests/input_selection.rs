@@ -41,6 +41,7 @@ fn input_selection_no_change_output() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     // TODO: Mandate that fee_per_byte is non-zero?
@@ -78,6 +79,7 @@ fn input_selection_no_utxo_inputs() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -88,6 +90,7 @@ fn input_selection_no_utxo_inputs() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -179,6 +182,7 @@ fn input_selection_insufficient_inputs() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -189,6 +193,7 @@ fn input_selection_insufficient_inputs() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -240,6 +245,7 @@ fn input_selection_no_utxo_outputs_with_change_output() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -315,6 +321,7 @@ fn input_selection_select_in_order() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -325,6 +332,7 @@ fn input_selection_select_in_order() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -427,6 +435,7 @@ fn input_selection_select_ascending() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -437,6 +446,7 @@ fn input_selection_select_ascending() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -553,6 +563,7 @@ fn input_selection_select_descending() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -563,6 +574,7 @@ fn input_selection_select_descending() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -680,6 +692,7 @@ fn input_selection_use_all() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -690,6 +703,7 @@ fn input_selection_use_all() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -813,6 +827,7 @@ fn input_selection_use_all_without_change_output() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
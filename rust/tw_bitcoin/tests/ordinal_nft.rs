@@ -1,13 +1,38 @@
 mod common;
 
+use bitcoin::taproot::LeafVersion;
+use bitcoin::{Address, PublicKey, ScriptBuf};
 use common::hex;
+use std::str::FromStr;
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{
+    InscriptionContent, InscriptionId, OrdinalNftInscription, OutputBuilder, SpendInfo,
+};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Utxo::Proto as UtxoProto;
 
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+
+fn ordinal_output(payload: &[u8], parent: &str) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::ordinal_inscribe(
+                Proto::mod_Output::OutputOrdinalInscription {
+                    inscribe_to: hex(ALICE_PUBKEY).into(),
+                    mime_type: "text/plain;charset=utf-8".into(),
+                    payload: payload.to_vec().into(),
+                    parent: parent.to_string().into(),
+                },
+            ),
+        }),
+        ..Default::default()
+    }
+}
+
 #[test]
 fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
     let coin = TestCoinContext::default();
@@ -39,9 +64,11 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
                     inscribe_to: alice_pubkey.as_slice().into(),
                     mime_type: "image/png".into(),
                     payload: hex(common::data::NFT_INSCRIPTION_IMAGE_DATA).into(),
+                    ..Default::default()
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -86,6 +113,7 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
                     inscribe_to: alice_pubkey.as_slice().into(),
                     mime_type: "image/png".into(),
                     payload: hex(common::data::NFT_INSCRIPTION_IMAGE_DATA).into(),
+                    ..Default::default()
                 },
             ),
         }),
@@ -99,6 +127,7 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -128,3 +157,326 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
+
+#[test]
+fn ordinal_nft_batch_inscription() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+
+    let first = InscriptionContent::new(b"text/plain;charset=utf-8", b"first inscription");
+    let second = InscriptionContent::new(b"application/json", br#"{"second":"inscription"}"#);
+
+    let batch = OrdinalNftInscription::new_batch(&[first, second], alice_pubkey).unwrap();
+    let program = batch.inscription().taproot_program();
+
+    // Both envelopes are stacked in the same Taproot program.
+    let single_first =
+        OrdinalNftInscription::new(first.mime_type, first.payload, alice_pubkey).unwrap();
+    let single_second =
+        OrdinalNftInscription::new(second.mime_type, second.payload, alice_pubkey).unwrap();
+
+    let mut expected = single_first.inscription().taproot_program().to_bytes();
+    expected.extend(single_second.inscription().taproot_program().to_bytes());
+    assert_eq!(program.as_bytes(), expected);
+
+    // A single merkle root and control block covers the whole program.
+    let spend_info = batch.inscription().spend_info();
+    let control_block = spend_info
+        .control_block(&(program.to_owned(), LeafVersion::TapScript))
+        .unwrap();
+    assert!(control_block.verify_taproot_commitment(
+        &secp256k1::Secp256k1::new(),
+        spend_info.output_key().to_inner(),
+        program,
+    ));
+
+    // An empty batch is rejected.
+    let err = OrdinalNftInscription::new_batch(&[], alice_pubkey)
+        .err()
+        .unwrap();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_missing_inscription
+    );
+}
+
+#[test]
+fn ordinal_nft_inscription_with_metadata_and_metaprotocol() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+
+    // CBOR: `{"name": "test"}`
+    let metadata = hex("a1646e616d656474657374");
+
+    let content = InscriptionContent::new(b"text/plain;charset=utf-8", b"Hello, world!")
+        .with_metadata(&metadata)
+        .with_metaprotocol("test-protocol");
+
+    let nft = OrdinalNftInscription::from_content(content, alice_pubkey).unwrap();
+    let program = nft.inscription().taproot_program().as_bytes();
+
+    // Metadata: `<5> <metadata>`
+    let mut expected = vec![0x01, 0x05, metadata.len() as u8];
+    expected.extend(&metadata);
+    assert!(program.windows(expected.len()).any(|w| w == expected));
+
+    // Metaprotocol: `<7> <"test-protocol">`
+    let mut expected = vec![0x01, 0x07, 13];
+    expected.extend(b"test-protocol");
+    assert!(program.windows(expected.len()).any(|w| w == expected));
+
+    // Invalid CBOR metadata (a map missing its entry).
+    let content = InscriptionContent::new(b"text/plain;charset=utf-8", b"Hello, world!")
+        .with_metadata(&[0xa1]);
+
+    let err = OrdinalNftInscription::from_content(content, alice_pubkey)
+        .err()
+        .unwrap();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_inscription_metadata
+    );
+}
+
+#[test]
+fn ordinal_nft_estimated_reveal_vbytes() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+
+    let small =
+        OrdinalNftInscription::new(b"text/plain;charset=utf-8", b"Hello", alice_pubkey).unwrap();
+    assert_eq!(small.inscription().taproot_program().len(), 41);
+    assert_eq!(small.estimated_reveal_vbytes(), 130);
+
+    // The payload is part of the witness, so each additional byte only adds
+    // a quarter vbyte.
+    let payload = vec![0; 10_000];
+    let large =
+        OrdinalNftInscription::new(b"text/plain;charset=utf-8", &payload, alice_pubkey).unwrap();
+    assert_eq!(large.inscription().taproot_program().len(), 10_094);
+    assert_eq!(large.estimated_reveal_vbytes(), 2_644);
+}
+
+#[test]
+fn ordinal_nft_commit_address() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let payload = hex(common::data::NFT_INSCRIPTION_IMAGE_DATA);
+
+    let nft = OrdinalNftInscription::new(
+        b"image/png",
+        &payload,
+        PublicKey::from_slice(&alice_pubkey).unwrap(),
+    )
+    .unwrap();
+
+    let address = nft.commit_address(bitcoin::Network::Bitcoin).unwrap();
+    assert!(address.starts_with("bc1p"));
+
+    // The address matches the output produced by the Ordinal builder.
+    let out = Proto::Output {
+        value: 31_100,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::ordinal_inscribe(
+                Proto::mod_Output::OutputOrdinalInscription {
+                    inscribe_to: alice_pubkey.as_slice().into(),
+                    mime_type: "image/png".into(),
+                    payload: payload.as_slice().into(),
+                    ..Default::default()
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&out).unwrap();
+    let script_pubkey = Address::from_str(&address)
+        .unwrap()
+        .assume_checked()
+        .script_pubkey();
+    assert_eq!(utxo.script_pubkey, script_pubkey.as_bytes());
+
+    // The commit output of the signed transaction (see above).
+    assert_eq!(
+        script_pubkey.as_bytes(),
+        hex("51202ac69a7e9dba801e9fcba826055917b84ca6fba4d51a29e47d478de603eedab6")
+    );
+}
+
+#[test]
+fn ordinal_nft_empty_payload() {
+    // An empty payload is rejected.
+    let err = OutputBuilder::utxo_from_proto(&ordinal_output(&[], "")).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_empty_inscription_payload
+    );
+
+    let pubkey = PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap();
+    let err = OrdinalNftInscription::new(b"text/plain;charset=utf-8", &[], pubkey).err();
+    assert_eq!(
+        err.map(Proto::Error::from),
+        Some(Proto::Error::Error_empty_inscription_payload)
+    );
+
+    // A single byte is fine.
+    OutputBuilder::utxo_from_proto(&ordinal_output(b"a", "")).unwrap();
+}
+
+#[test]
+fn ordinal_nft_inscription_commit_outputs() {
+    let alice_pubkey = PublicKey::from_slice(&hex(
+        "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb",
+    ))
+    .unwrap();
+
+    let contents = [
+        InscriptionContent::new(b"text/plain;charset=utf-8", b"first"),
+        InscriptionContent::new(b"text/plain;charset=utf-8", b"second"),
+        InscriptionContent::new(b"text/plain;charset=utf-8", b"third"),
+    ];
+
+    let outputs =
+        OutputBuilder::inscription_commit_outputs(&contents, alice_pubkey, 1_000).unwrap();
+    assert_eq!(outputs.len(), 3);
+
+    for (output, content) in outputs.iter().zip(&contents) {
+        let nft = OrdinalNftInscription::from_content(*content, alice_pubkey).unwrap();
+
+        assert_eq!(output.value, 1_000);
+        assert!(ScriptBuf::from_bytes(output.script_pubkey.to_vec()).is_v1_p2tr());
+        assert_eq!(
+            output.taproot_payload.as_ref(),
+            nft.inscription().taproot_program().as_bytes()
+        );
+        assert_eq!(
+            output.control_block.as_ref(),
+            nft.inscription().control_block().serialize()
+        );
+    }
+
+    // Each inscription commits to a distinct scriptPubkey.
+    assert_ne!(outputs[0].script_pubkey, outputs[1].script_pubkey);
+    assert_ne!(outputs[0].script_pubkey, outputs[2].script_pubkey);
+    assert_ne!(outputs[1].script_pubkey, outputs[2].script_pubkey);
+
+    // The index of the invalid inscription is reported.
+    let contents = [
+        InscriptionContent::new(b"text/plain;charset=utf-8", b"first"),
+        InscriptionContent::new(b"text/plain;charset=utf-8", b""),
+    ];
+
+    let err =
+        OutputBuilder::inscription_commit_outputs(&contents, alice_pubkey, 1_000).unwrap_err();
+    assert_eq!(err.index, 1);
+    assert_eq!(
+        Proto::Error::from(err.error),
+        Proto::Error::Error_empty_inscription_payload
+    );
+}
+
+#[test]
+fn ordinal_nft_inscription_too_large() {
+    // The payload results in a 399,517 byte taproot program, the largest
+    // standard witness element.
+    let utxo = OutputBuilder::utxo_from_proto(&ordinal_output(&vec![0; 397_190], "")).unwrap();
+    assert_eq!(utxo.taproot_payload.len(), 399_517);
+
+    // One more byte exceeds the standard witness element limit.
+    let err = OutputBuilder::utxo_from_proto(&ordinal_output(&vec![0; 397_191], "")).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_inscription_too_large
+    );
+}
+
+#[test]
+fn ordinal_nft_inscription_with_parent() {
+    let parent_txid = "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799";
+    let parent_txid_reversed: Vec<u8> = hex(parent_txid).into_iter().rev().collect();
+
+    let output = |parent: String| ordinal_output(b"child", &parent);
+
+    // The trailing zero bytes of the index are omitted: `<3> <txid>`
+    let utxo = OutputBuilder::utxo_from_proto(&output(format!("{parent_txid}i0"))).unwrap();
+    let mut expected = hex("010320");
+    expected.extend_from_slice(&parent_txid_reversed);
+    assert!(utxo
+        .taproot_payload
+        .windows(expected.len())
+        .any(|window| window == expected.as_slice()));
+
+    // `<3> <txid || 0x01>`
+    let utxo = OutputBuilder::utxo_from_proto(&output(format!("{parent_txid}i1"))).unwrap();
+    let mut expected = hex("010321");
+    expected.extend_from_slice(&parent_txid_reversed);
+    expected.push(0x01);
+    assert!(utxo
+        .taproot_payload
+        .windows(expected.len())
+        .any(|window| window == expected.as_slice()));
+
+    // The parent changes the commitment.
+    let without_parent = OutputBuilder::utxo_from_proto(&output(String::new())).unwrap();
+    assert_ne!(without_parent.script_pubkey, utxo.script_pubkey);
+
+    let parent = InscriptionId::from_str(&format!("{parent_txid}i1")).unwrap();
+    assert_eq!(parent.index, 1);
+    assert_eq!(parent.serialize().len(), 33);
+
+    for invalid in [
+        parent_txid.to_string(),
+        format!("{parent_txid}i"),
+        format!("{parent_txid}i+1"),
+        format!("{parent_txid}i4294967296"),
+        format!("{}i0", &parent_txid[2..]),
+        format!("{}i0", parent_txid.replace('6', "x")),
+    ] {
+        let err = OutputBuilder::utxo_from_proto(&output(invalid)).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(err),
+            Proto::Error::Error_invalid_inscription_id
+        );
+    }
+}
+
+#[test]
+fn ordinal_nft_spend_info() {
+    let alice_pubkey = PublicKey::from_slice(&hex(
+        "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb",
+    ))
+    .unwrap();
+
+    let content = InscriptionContent::new(b"text/plain;charset=utf-8", b"hello");
+    let outputs =
+        OutputBuilder::inscription_commit_outputs(&[content], alice_pubkey, 1_000).unwrap();
+
+    let spend_info = SpendInfo::from_txout(&outputs[0]).unwrap();
+
+    let nft = OrdinalNftInscription::from_content(content, alice_pubkey).unwrap();
+    let inscription = nft.inscription();
+    assert_eq!(spend_info.script.as_script(), inscription.taproot_program());
+    assert_eq!(spend_info.control_block, inscription.control_block());
+    assert_eq!(spend_info.leaf_version, LeafVersion::TapScript);
+    assert_eq!(
+        Some(spend_info.merkle_root),
+        inscription.spend_info().merkle_root()
+    );
+
+    // The control block must commit to the script.
+    let mut tampered = outputs[0].clone();
+    tampered.taproot_payload = hex("51").into();
+    let err = SpendInfo::from_txout(&tampered).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_control_block
+    );
+
+    // Outputs without a script-path have no spend info.
+    let mut key_path = outputs[0].clone();
+    key_path.control_block = Default::default();
+    let err = SpendInfo::from_txout(&key_path).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_missing_taproot_spend_info
+    );
+}
@@ -0,0 +1,541 @@
+mod common;
+
+use bitcoin::{PublicKey, ScriptBuf};
+use common::{hex, ONE_BTC};
+use secp256k1::XOnlyPublicKey;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::transactions::{OutputBuilder, RecipientKind};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+// `OP_TRUE`
+const REDEEM_SCRIPT: &str = "51";
+
+// Convenience function: builds the output and classifies its scriptPubkey.
+fn build_and_classify(variant: ProtoOutputBuilder) -> RecipientKind {
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder { variant }),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    OutputBuilder::classify_script(&utxo.script_pubkey)
+}
+
+#[test]
+fn output_builders_produce_intended_script() {
+    let pubkey = hex(PUBKEY);
+    let redeem_script = hex(REDEEM_SCRIPT);
+
+    let kind = build_and_classify(ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+        to_address: ProtoPubkeyOrHash::pubkey(pubkey.as_slice().into()),
+    }));
+    assert_eq!(kind, RecipientKind::P2pkh);
+
+    let kind = build_and_classify(ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+        to_address: ProtoPubkeyOrHash::pubkey(pubkey.as_slice().into()),
+    }));
+    assert_eq!(kind, RecipientKind::P2wpkh);
+
+    let kind = build_and_classify(ProtoOutputBuilder::p2sh(
+        Proto::mod_Output::OutputRedeemScriptOrHash {
+            variant: ProtoOutputRedeemScriptOrHashBuilder::redeem_script(
+                redeem_script.as_slice().into(),
+            ),
+        },
+    ));
+    assert_eq!(kind, RecipientKind::P2sh);
+
+    let kind = build_and_classify(ProtoOutputBuilder::p2wsh(
+        Proto::mod_Output::OutputRedeemScriptOrHash {
+            variant: ProtoOutputRedeemScriptOrHashBuilder::redeem_script(
+                redeem_script.as_slice().into(),
+            ),
+        },
+    ));
+    assert_eq!(kind, RecipientKind::P2wsh);
+
+    let kind = build_and_classify(ProtoOutputBuilder::p2tr_key_path(pubkey.as_slice().into()));
+    assert_eq!(kind, RecipientKind::P2tr);
+}
+
+#[test]
+fn op_return_output_with_multiple_pushes() {
+    let output = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::op_return(Proto::mod_Output::OutputOpReturn {
+                data: vec![
+                    b"omni".as_slice().into(),
+                    hex("00000000000000010000000000000064").into(),
+                ],
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+
+    // `OP_RETURN <"omni"> <payload>`
+    assert_eq!(
+        utxo.script_pubkey,
+        hex("6a046f6d6e691000000000000000010000000000000064")
+    );
+    assert_eq!(
+        OutputBuilder::classify_script(&utxo.script_pubkey),
+        RecipientKind::OpReturn
+    );
+}
+
+#[test]
+fn op_return_output_too_large() {
+    // 40 + 41 bytes of data exceed the 80-byte standardness limit.
+    let output = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::op_return(Proto::mod_Output::OutputOpReturn {
+                data: vec![vec![1; 40].into(), vec![2; 41].into()],
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let err = OutputBuilder::utxo_from_proto(&output).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_op_return_too_large
+    );
+}
+
+// Convenience function: builds the output from the descriptor and returns its
+// scriptPubkey.
+fn descriptor_script(desc: &str) -> ScriptBuf {
+    let output = OutputBuilder::from_descriptor(desc, ONE_BTC).unwrap();
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    ScriptBuf::from_bytes(utxo.script_pubkey.to_vec())
+}
+
+#[test]
+fn output_from_descriptor() {
+    let secp = secp256k1::Secp256k1::new();
+    let pubkey = PublicKey::from_slice(&hex(PUBKEY)).unwrap();
+    let wpubkey_hash = pubkey.wpubkey_hash().unwrap();
+
+    let script = descriptor_script(&format!("pkh({PUBKEY})"));
+    assert_eq!(script, ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()));
+
+    let script = descriptor_script(&format!("wpkh({PUBKEY})"));
+    assert_eq!(script, ScriptBuf::new_v0_p2wpkh(&wpubkey_hash));
+
+    // With a checksum.
+    let script = descriptor_script(&format!("wpkh({PUBKEY})#lhvcawaq"));
+    assert_eq!(script, ScriptBuf::new_v0_p2wpkh(&wpubkey_hash));
+
+    let script = descriptor_script(&format!("sh(wpkh({PUBKEY}))"));
+    let redeem_script = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash);
+    assert_eq!(script, ScriptBuf::new_p2sh(&redeem_script.script_hash()));
+
+    let script = descriptor_script(&format!("sh(pkh({PUBKEY}))"));
+    let redeem_script = ScriptBuf::new_p2pkh(&pubkey.pubkey_hash());
+    assert_eq!(script, ScriptBuf::new_p2sh(&redeem_script.script_hash()));
+
+    // Taproot accepts both, compressed and x-only keys.
+    let xonly = XOnlyPublicKey::from(pubkey.inner);
+    let expected = ScriptBuf::new_v1_p2tr(&secp, xonly, None);
+    assert_eq!(descriptor_script(&format!("tr({PUBKEY})")), expected);
+    assert_eq!(
+        descriptor_script(&format!("tr({})", &PUBKEY[2..])),
+        expected
+    );
+}
+
+#[test]
+fn output_from_invalid_descriptor() {
+    let error =
+        |desc: &str| Proto::Error::from(OutputBuilder::from_descriptor(desc, ONE_BTC).unwrap_err());
+
+    // Invalid checksum.
+    assert_eq!(
+        error(&format!("wpkh({PUBKEY})#lhvcawap")),
+        Proto::Error::Error_invalid_descriptor
+    );
+    // Malformed expression.
+    assert_eq!(
+        error(&format!("wpkh({PUBKEY}")),
+        Proto::Error::Error_invalid_descriptor
+    );
+    // Invalid public key.
+    assert_eq!(
+        error("pkh(02deadbeef)"),
+        Proto::Error::Error_invalid_descriptor
+    );
+
+    // Unsupported descriptors.
+    let unsupported = [
+        format!("multi(1,{PUBKEY})"),
+        format!("sh(multi(1,{PUBKEY}))"),
+        format!("tr({PUBKEY},pk({PUBKEY}))"),
+        format!("wpkh([d34db33f/84h/0h/0h]{PUBKEY})"),
+        "wpkh(xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8/0/*)".to_string(),
+    ];
+
+    for desc in unsupported {
+        assert_eq!(error(&desc), Proto::Error::Error_unsupported_descriptor);
+    }
+}
+
+#[test]
+fn output_label_passthrough() {
+    let variant = ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+        to_address: ProtoPubkeyOrHash::pubkey(hex(PUBKEY).into()),
+    });
+
+    let unlabeled = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: variant.clone(),
+        }),
+        ..Default::default()
+    };
+
+    let labeled = Proto::Output {
+        label: "Savings".into(),
+        ..unlabeled.clone()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&labeled).unwrap();
+    assert_eq!(utxo.label, "Savings");
+
+    // The label does not affect the script or the consensus encoding.
+    let unlabeled_utxo = OutputBuilder::utxo_from_proto(&unlabeled).unwrap();
+    assert!(unlabeled_utxo.label.is_empty());
+    assert_eq!(utxo.script_pubkey, unlabeled_utxo.script_pubkey);
+    assert_eq!(
+        OutputBuilder::serialize_txout(&utxo),
+        OutputBuilder::serialize_txout(&unlabeled_utxo)
+    );
+
+    // The label is also preserved for outputs derived from an address.
+    let from_address = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::from_address(
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3".into(),
+        ),
+        label: "Rent".into(),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&from_address).unwrap();
+    assert_eq!(utxo.label, "Rent");
+}
+
+#[test]
+fn output_sighash_hint_passthrough() {
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(PUBKEY).into()),
+            }),
+        }),
+        sighash_hint: UtxoProto::SighashType::Single,
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    assert_eq!(utxo.sighash_hint, UtxoProto::SighashType::Single);
+
+    // The hint does not affect the consensus encoding.
+    let default_utxo = OutputBuilder::utxo_from_proto(&Proto::Output {
+        sighash_hint: Default::default(),
+        ..output
+    })
+    .unwrap();
+    assert_eq!(
+        default_utxo.sighash_hint,
+        UtxoProto::SighashType::UseDefault
+    );
+    assert_eq!(
+        OutputBuilder::serialize_txout(&utxo),
+        OutputBuilder::serialize_txout(&default_utxo)
+    );
+
+    // The hint is also preserved for outputs derived from an address.
+    let from_address = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::from_address(
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3".into(),
+        ),
+        sighash_hint: UtxoProto::SighashType::Single,
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&from_address).unwrap();
+    assert_eq!(utxo.sighash_hint, UtxoProto::SighashType::Single);
+}
+
+#[test]
+fn script_hash_length_mismatch() {
+    let build = |variant: ProtoOutputBuilder| {
+        let output = Proto::Output {
+            value: ONE_BTC,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant,
+            }),
+            ..Default::default()
+        };
+
+        OutputBuilder::utxo_from_proto(&output)
+    };
+
+    let script_hash = [1; 20];
+    let wscript_hash = [1; 32];
+
+    // A 32-byte (P2WSH) hash passed to P2SH.
+    let err = build(ProtoOutputBuilder::p2sh(
+        Proto::mod_Output::OutputRedeemScriptOrHash {
+            variant: ProtoOutputRedeemScriptOrHashBuilder::hash(wscript_hash.as_slice().into()),
+        },
+    ))
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_redeem_script_hash_length
+    );
+
+    // A 20-byte (P2SH) hash passed to P2WSH.
+    let err = build(ProtoOutputBuilder::p2wsh(
+        Proto::mod_Output::OutputRedeemScriptOrHash {
+            variant: ProtoOutputRedeemScriptOrHashBuilder::hash(script_hash.as_slice().into()),
+        },
+    ))
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_witness_redeem_script_hash_length
+    );
+
+    // The correct lengths are accepted.
+    build(ProtoOutputBuilder::p2sh(
+        Proto::mod_Output::OutputRedeemScriptOrHash {
+            variant: ProtoOutputRedeemScriptOrHashBuilder::hash(script_hash.as_slice().into()),
+        },
+    ))
+    .unwrap();
+    build(ProtoOutputBuilder::p2wsh(
+        Proto::mod_Output::OutputRedeemScriptOrHash {
+            variant: ProtoOutputRedeemScriptOrHashBuilder::hash(wscript_hash.as_slice().into()),
+        },
+    ))
+    .unwrap();
+}
+
+#[test]
+fn pubkey_hash_length_mismatch() {
+    let build = |variant: ProtoOutputBuilder| {
+        let output = Proto::Output {
+            value: ONE_BTC,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant,
+            }),
+            ..Default::default()
+        };
+
+        OutputBuilder::utxo_from_proto(&output)
+    };
+
+    let to_hash = |hash: &[u8]| Proto::ToPublicKeyOrHash {
+        to_address: ProtoPubkeyOrHash::hash(hash.to_vec().into()),
+    };
+
+    let pubkey_hash = [1; 20];
+    let wscript_hash = [1; 32];
+
+    // A 32-byte (P2WSH) hash passed to P2PKH and P2WPKH.
+    let err = build(ProtoOutputBuilder::p2pkh(to_hash(&wscript_hash))).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_pubkey_hash_length
+    );
+
+    let err = build(ProtoOutputBuilder::p2wpkh(to_hash(&wscript_hash))).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_witness_pubkey_hash_length
+    );
+
+    // The correct lengths are accepted.
+    build(ProtoOutputBuilder::p2pkh(to_hash(&pubkey_hash))).unwrap();
+    build(ProtoOutputBuilder::p2wpkh(to_hash(&pubkey_hash))).unwrap();
+}
+
+#[test]
+fn output_timelock_hints() {
+    // `<500000> OP_CHECKLOCKTIMEVERIFY OP_DROP <pubkey> OP_CHECKSIG`
+    let cltv_script = hex(&format!("0320a107b17521{PUBKEY}ac"));
+
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(cltv_script.into()),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    assert_eq!(utxo.min_locktime, 500_000);
+    assert_eq!(utxo.min_sequence, 0);
+
+    // `<144> OP_CHECKSEQUENCEVERIFY OP_DROP <pubkey> OP_CHECKSIG`
+    let csv_script = hex(&format!("029000b27521{PUBKEY}ac"));
+
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wsh(Proto::mod_Output::OutputRedeemScriptOrHash {
+                variant: ProtoOutputRedeemScriptOrHashBuilder::redeem_script(csv_script.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    assert_eq!(utxo.min_locktime, 0);
+    assert_eq!(utxo.min_sequence, 144);
+
+    // No timelocks.
+    let utxo = OutputBuilder::utxo_from_proto(&Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(hex(REDEEM_SCRIPT).into()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(utxo.min_locktime, 0);
+    assert_eq!(utxo.min_sequence, 0);
+}
+
+#[test]
+fn output_witness_program() {
+    let witness_program = |version: u32, program: Vec<u8>| Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::witness_program(Proto::mod_Output::OutputWitnessProgram {
+                version,
+                program: program.into(),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // `OP_2 <32 bytes>`
+    let program = vec![0xab; 32];
+    let utxo = OutputBuilder::utxo_from_proto(&witness_program(2, program.clone())).unwrap();
+    assert_eq!(
+        utxo.script_pubkey.as_ref(),
+        [&[0x52, 0x20], program.as_slice()].concat()
+    );
+    assert_eq!(
+        OutputBuilder::classify_script(&utxo.script_pubkey),
+        RecipientKind::NonStandard
+    );
+
+    // `OP_16 <2 bytes>` and `OP_16 <40 bytes>`
+    let utxo = OutputBuilder::utxo_from_proto(&witness_program(16, vec![0xab; 2])).unwrap();
+    assert_eq!(utxo.script_pubkey.as_ref(), hex("6002abab"));
+    let utxo = OutputBuilder::utxo_from_proto(&witness_program(16, vec![0xab; 40])).unwrap();
+    assert_eq!(utxo.script_pubkey.len(), 42);
+
+    // Version 0 programs are P2WPKH or P2WSH.
+    let utxo = OutputBuilder::utxo_from_proto(&witness_program(0, vec![0xab; 20])).unwrap();
+    assert_eq!(
+        OutputBuilder::classify_script(&utxo.script_pubkey),
+        RecipientKind::P2wpkh
+    );
+
+    for (version, len) in [(2, 1), (2, 41), (17, 32), (0, 0), (0, 25)] {
+        let err =
+            OutputBuilder::utxo_from_proto(&witness_program(version, vec![0xab; len])).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(err),
+            Proto::Error::Error_invalid_witness_program
+        );
+    }
+}
+
+#[test]
+fn output_htlc() {
+    const RECIPIENT_PUBKEY: &str =
+        "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+    // SHA256 of `secret`.
+    const HASH: &str = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+
+    let htlc = |hash: &str, recipient_pubkey: &str| Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::htlc(Proto::mod_Output::OutputHtlc {
+                hash: hex(hash).into(),
+                recipient_pubkey: hex(recipient_pubkey).into(),
+                timeout: 144,
+                refund_pubkey: hex(PUBKEY).into(),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // P2WSH of `OP_IF OP_SHA256 <hash> OP_EQUALVERIFY <recipient> OP_ELSE <144>
+    // OP_CSV OP_DROP <refund> OP_ENDIF OP_CHECKSIG`
+    let utxo = OutputBuilder::utxo_from_proto(&htlc(HASH, RECIPIENT_PUBKEY)).unwrap();
+    assert_eq!(
+        utxo.script_pubkey.as_ref(),
+        hex("0020e6c0607c62600d33e32464505c80d2f8b1d5086c181fadebb1506703e71fddd6")
+    );
+    assert_eq!(utxo.min_sequence, 144);
+
+    let err = OutputBuilder::utxo_from_proto(&htlc(&HASH[..62], RECIPIENT_PUBKEY)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_htlc_hash
+    );
+
+    let uncompressed = "045a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f\
+        5c6875df1999330d02795096fe4bcbe6ea141191b8b060ae51215e1aeb0931e4";
+    let err = OutputBuilder::utxo_from_proto(&htlc(HASH, uncompressed)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_uncompressed_public_key
+    );
+}
+
+#[test]
+fn output_from_xpub() {
+    // The master key of BIP32 test vector 1.
+    let xpub = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    let network = bitcoin::Network::Bitcoin;
+
+    // Derive the P2WPKH output of `m/0`.
+    let output = OutputBuilder::from_xpub(xpub, 0, RecipientKind::P2wpkh, network).unwrap();
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    assert_eq!(
+        OutputBuilder::classify_script(&utxo.script_pubkey),
+        RecipientKind::P2wpkh
+    );
+
+    let address = OutputBuilder::address_from_script(&utxo.script_pubkey, network).unwrap();
+    assert_eq!(address, "bc1qnnypkcfrvu3e9dhzeggpn4kh622l4cq7c5sghz");
+
+    // Hardened indices can't be derived from an xpub.
+    let err =
+        OutputBuilder::from_xpub(xpub, 0x80000000, RecipientKind::P2wpkh, network).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_derivation_index
+    );
+
+    // The xpub is a mainnet key.
+    let err = OutputBuilder::from_xpub(xpub, 0, RecipientKind::P2wpkh, bitcoin::Network::Testnet)
+        .unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_invalid_xpub);
+
+    let err = OutputBuilder::from_xpub("xpub", 0, RecipientKind::P2wpkh, network).unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_invalid_xpub);
+}
@@ -0,0 +1,284 @@
+mod common;
+
+use common::{hex, MINER_FEE, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{
+    DustPolicy, DustThresholds, OutputBuilder, StandardnessPolicy,
+};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const BOB_PUBKEY: &str = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+
+// `OP_RETURN <"hello">`
+const OP_RETURN_SCRIPT: &str = "6a0568656c6c6f";
+
+fn sign_with_outputs(outputs: Vec<Proto::Output>) -> Proto::SigningOutput<'static> {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex(ALICE_PRIVATE_KEY);
+    let alice_pubkey = hex(ALICE_PUBKEY);
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: ONE_BTC * 50,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2pkh(alice_pubkey.into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.into(),
+        inputs: vec![tx1],
+        outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    BitcoinEntry.sign(&coin, signing)
+}
+
+fn p2wpkh_output(value: u64) -> Proto::Output<'static> {
+    Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(BOB_PUBKEY).into()),
+            }),
+        }),
+        ..Default::default()
+    }
+}
+
+fn op_return_output(value: u64) -> Proto::Output<'static> {
+    Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(hex(OP_RETURN_SCRIPT).into()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn zero_value_p2wpkh_output() {
+    let signed = sign_with_outputs(vec![p2wpkh_output(0)]);
+    assert_eq!(signed.error, Proto::Error::Error_zero_value_output);
+}
+
+#[test]
+fn zero_value_op_return_output() {
+    let signed = sign_with_outputs(vec![
+        p2wpkh_output(ONE_BTC * 50 - MINER_FEE),
+        op_return_output(0),
+    ]);
+    assert_eq!(signed.error, Proto::Error::OK);
+}
+
+#[test]
+fn positive_value_op_return_output() {
+    let signed = sign_with_outputs(vec![
+        p2wpkh_output(ONE_BTC * 50 - MINER_FEE - 1_000),
+        op_return_output(1_000),
+    ]);
+    assert_eq!(signed.error, Proto::Error::Error_op_return_nonzero_value);
+}
+
+#[test]
+fn dust_policy_bitcoin_core() {
+    let utxo = OutputBuilder::utxo_from_proto(&p2wpkh_output(293)).unwrap();
+    let err = OutputBuilder::validate_dust(&utxo, &DustPolicy::BitcoinCore).unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_dust_output);
+
+    let utxo = OutputBuilder::utxo_from_proto(&p2wpkh_output(294)).unwrap();
+    OutputBuilder::validate_dust(&utxo, &DustPolicy::BitcoinCore).unwrap();
+
+    // OP_RETURN outputs are never dust.
+    let utxo = OutputBuilder::utxo_from_proto(&op_return_output(0)).unwrap();
+    OutputBuilder::validate_dust(&utxo, &DustPolicy::BitcoinCore).unwrap();
+}
+
+#[test]
+fn dust_policy_disabled() {
+    let utxo = OutputBuilder::utxo_from_proto(&p2wpkh_output(1)).unwrap();
+    OutputBuilder::validate_dust(&utxo, &DustPolicy::Disabled).unwrap();
+}
+
+#[test]
+fn dust_policy_custom() {
+    let policy = DustPolicy::Custom(DustThresholds {
+        p2wpkh: 1_000,
+        ..DustThresholds::BITCOIN_CORE
+    });
+
+    let utxo = OutputBuilder::utxo_from_proto(&p2wpkh_output(999)).unwrap();
+    let err = OutputBuilder::validate_dust(&utxo, &policy).unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_dust_output);
+
+    let utxo = OutputBuilder::utxo_from_proto(&p2wpkh_output(1_000)).unwrap();
+    OutputBuilder::validate_dust(&utxo, &policy).unwrap();
+}
+
+#[test]
+fn output_set_total_value() {
+    let change = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let recipients = [p2wpkh_output(10_000), op_return_output(0)];
+
+    let (outputs, total) = OutputBuilder::build_output_set(&recipients, Some(&change)).unwrap();
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(total, 10_000 + ONE_BTC);
+
+    // The change output comes last.
+    assert_eq!(outputs[2].value, ONE_BTC);
+
+    // Without change.
+    let (outputs, total) = OutputBuilder::build_output_set(&recipients, None).unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(total, 10_000);
+}
+
+#[test]
+fn output_set_invalid() {
+    // The change output pays to the same script as a recipient.
+    let err = OutputBuilder::build_output_set(&[p2wpkh_output(10_000)], Some(&p2wpkh_output(1)))
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_duplicate_change_output
+    );
+
+    // The total output value overflows.
+    let err = OutputBuilder::build_output_set(&[p2wpkh_output(u64::MAX), p2wpkh_output(1)], None)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_output_value_overflow
+    );
+}
+
+#[test]
+fn output_set_only_unspendable_outputs() {
+    // A single OP_RETURN output.
+    let err = OutputBuilder::build_output_set(&[op_return_output(0)], None).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_only_unspendable_outputs
+    );
+
+    // Unless data-only transactions are explicitly allowed.
+    let (outputs, total) =
+        OutputBuilder::build_data_output_set(&[op_return_output(0)], None).unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(total, 0);
+
+    // An OP_RETURN output alongside a payment.
+    let recipients = [op_return_output(0), p2wpkh_output(10_000)];
+    let (outputs, total) = OutputBuilder::build_output_set(&recipients, None).unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(total, 10_000);
+
+    // The change output is spendable as well.
+    let (outputs, _) =
+        OutputBuilder::build_output_set(&[op_return_output(0)], Some(&p2wpkh_output(1_000)))
+            .unwrap();
+    assert_eq!(outputs.len(), 2);
+}
+
+#[test]
+fn sparse_output_set() {
+    let empty = Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::None,
+        ..Default::default()
+    };
+
+    let recipients = [p2wpkh_output(10_000), empty.clone(), op_return_output(0)];
+
+    // Empty recipients are skipped.
+    let (outputs, total) = OutputBuilder::build_sparse_output_set(&recipients, None).unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(total, 10_000);
+    assert_eq!(outputs[0].value, 10_000);
+    assert_eq!(outputs[1].value, 0);
+
+    // Building the output set (or a single output) still fails.
+    let err = OutputBuilder::build_output_set(&recipients, None).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_missing_recipient
+    );
+
+    let err = OutputBuilder::utxo_from_proto(&empty).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_missing_recipient
+    );
+}
+
+#[test]
+fn standardness_policy_strict() {
+    let custom_output = |script: Vec<u8>| Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(script.into()),
+        ..Default::default()
+    };
+
+    // `OP_RETURN <"hello">` is standard.
+    let utxo = OutputBuilder::utxo_from_proto(&op_return_output(0)).unwrap();
+    OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::Strict).unwrap();
+
+    // The script exceeds 10,000 bytes (`OP_NOP` x 10,001).
+    let utxo = OutputBuilder::utxo_from_proto(&custom_output(vec![0x61; 10_001])).unwrap();
+    let err = OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::Strict).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_script_too_large
+    );
+
+    // Exactly 10,000 bytes are fine.
+    let utxo = OutputBuilder::utxo_from_proto(&custom_output(vec![0x61; 10_000])).unwrap();
+    OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::Strict).unwrap();
+
+    // `OP_1 OP_1 OP_CAT`
+    let utxo = OutputBuilder::utxo_from_proto(&custom_output(hex("51517e"))).unwrap();
+    let err = OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::Strict).unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_disabled_opcode);
+
+    // The disabled opcode within a push is just data: `<0x7e>`
+    let utxo = OutputBuilder::utxo_from_proto(&custom_output(hex("017e"))).unwrap();
+    OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::Strict).unwrap();
+}
+
+#[test]
+fn standardness_policy_disabled() {
+    let output = Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(hex("51517e").into()),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::Disabled).unwrap();
+    OutputBuilder::validate_standardness(&utxo, &StandardnessPolicy::default()).unwrap();
+}
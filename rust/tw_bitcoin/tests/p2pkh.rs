@@ -41,6 +41,7 @@ fn coin_entry_sign_input_p2pkh_output_p2pkh() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
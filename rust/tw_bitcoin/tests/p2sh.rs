@@ -1,11 +1,13 @@
 mod common;
 
+use bitcoin::opcodes::all::OP_CHECKMULTISIG;
 use bitcoin::script::PushBytesBuf;
 use bitcoin::{PublicKey, ScriptBuf};
 use common::{hex, MINER_FEE, ONE_BTC};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
 use tw_bitcoin::modules::signer::Signer;
+use tw_bitcoin::modules::transactions::{OutputBuilder, RecipientKind};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -51,6 +53,7 @@ fn coin_entry_sign_input_p2pkh_output_p2sh() {
                 ),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -93,6 +96,7 @@ fn coin_entry_sign_input_p2pkh_output_p2sh() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let mut signing = Proto::SigningInput {
@@ -145,3 +149,73 @@ fn coin_entry_sign_input_p2pkh_output_p2sh() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "020000000182bd495ffd6799180d1eb65a8e6d346519120d2f445bfab69f871a417ab7995d000000008447304402207aad4b72c6d78c81a1e795325bd5ddb449f0a1363205903f5e37950e6b89054102202aaf4dd919700d21fe2431352df99c434378bd0d46b778b445079579300effdf0121037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf1976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88acffffffff01806de729010000001976a914e4c1ea86373d554b8f4efff2cfb0001ea19124d288ac00000000");
 }
+
+#[test]
+fn decode_p2sh_redeem_p2wpkh() {
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let alice_pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+
+    // P2WPKH nested in P2SH.
+    let redeem_script = ScriptBuf::new_v0_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+
+    let kind = OutputBuilder::decode_p2sh_redeem(redeem_script.as_bytes()).unwrap();
+    assert_eq!(kind, RecipientKind::P2wpkh);
+}
+
+#[test]
+fn decode_p2sh_redeem_multisig() {
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+    let carol_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    // 2-of-3 multisig redeem script.
+    let redeem_script = ScriptBuf::builder()
+        .push_int(2)
+        .push_key(&PublicKey::from_slice(&alice_pubkey).unwrap())
+        .push_key(&PublicKey::from_slice(&bob_pubkey).unwrap())
+        .push_key(&PublicKey::from_slice(&carol_pubkey).unwrap())
+        .push_int(3)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+
+    let kind = OutputBuilder::decode_p2sh_redeem(redeem_script.as_bytes()).unwrap();
+    assert_eq!(
+        kind,
+        RecipientKind::Multisig {
+            required: 2,
+            total: 3
+        }
+    );
+
+    // Empty redeem scripts are rejected.
+    let err = OutputBuilder::decode_p2sh_redeem(&[]).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_redeem_script
+    );
+}
+
+#[test]
+fn verify_p2sh_redeem_script() {
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    let alice_redeem_script =
+        ScriptBuf::new_p2pkh(&PublicKey::from_slice(&alice_pubkey).unwrap().pubkey_hash());
+    let bob_redeem_script =
+        ScriptBuf::new_p2pkh(&PublicKey::from_slice(&bob_pubkey).unwrap().pubkey_hash());
+
+    let hash = alice_redeem_script.script_hash();
+
+    // Matching pair.
+    assert!(OutputBuilder::verify_p2sh_redeem(
+        &hash,
+        alice_redeem_script.as_bytes()
+    ));
+
+    // Mismatching pair.
+    assert!(!OutputBuilder::verify_p2sh_redeem(
+        &hash,
+        bob_redeem_script.as_bytes()
+    ));
+}
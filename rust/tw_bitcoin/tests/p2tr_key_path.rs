@@ -1,8 +1,13 @@
 mod common;
 
+use bitcoin::taproot::TapNodeHash;
+use bitcoin::ScriptBuf;
 use common::{hex, MINER_FEE, ONE_BTC};
+use secp256k1::hashes::Hash;
+use secp256k1::{Parity, XOnlyPublicKey};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::OutputBuilder;
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -38,6 +43,7 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(bob_pubkey.as_slice().into()),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -78,6 +84,7 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(alice_pubkey.as_slice().into()),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -96,3 +103,49 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "02000000000101ac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0000000000ffffffff01806de72901000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c20140ec2d3910d41506b60aaa20520bb72f15e2d2cbd97e3a8e26ee7bad5f4c56b0f2fb0ceaddac33cb2813a33ba017ba6b1d011bab74a0426f12a2bcf47b4ed5bc8600000000");
 }
+
+#[test]
+fn taproot_address_key_path_only() {
+    // Test vector from BIP86 (m/86'/0'/0'/0/0).
+    let internal_key = hex("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115");
+    let internal_key = XOnlyPublicKey::from_slice(&internal_key).unwrap();
+
+    let address =
+        OutputBuilder::taproot_address(internal_key, None, bitcoin::Network::Bitcoin).unwrap();
+    assert_eq!(
+        address,
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+    );
+}
+
+#[test]
+fn tweak_taproot_key() {
+    let secp = secp256k1::Secp256k1::new();
+
+    // Test vector from BIP86 (m/86'/0'/0'/0/0).
+    let internal_key = hex("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115");
+    let internal_key = XOnlyPublicKey::from_slice(&internal_key).unwrap();
+
+    // Key-path only.
+    let (output_key, parity) = OutputBuilder::tweak_taproot_key(internal_key, None);
+    let script = ScriptBuf::new_v1_p2tr(&secp, internal_key, None);
+
+    assert_eq!(output_key.serialize(), script.as_bytes()[2..]);
+    assert_eq!(
+        output_key.serialize().to_vec(),
+        hex("a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c")
+    );
+    assert_eq!(parity, Parity::Odd);
+
+    // With a script tree, consisting of a single `OP_TRUE` leaf.
+    let merkle_root = TapNodeHash::from_slice(&hex(
+        "a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675",
+    ))
+    .unwrap();
+
+    let (output_key, parity) = OutputBuilder::tweak_taproot_key(internal_key, Some(merkle_root));
+    let script = ScriptBuf::new_v1_p2tr(&secp, internal_key, Some(merkle_root));
+
+    assert_eq!(output_key.serialize(), script.as_bytes()[2..]);
+    assert_eq!(parity, Parity::Odd);
+}
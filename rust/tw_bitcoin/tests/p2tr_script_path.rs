@@ -1,11 +1,17 @@
 mod common;
 
-use bitcoin::taproot::LeafVersion;
-use bitcoin::PublicKey;
+use bitcoin::key::TapTweak;
+use bitcoin::opcodes::all::OP_CHECKSIG;
+use bitcoin::taproot::{LeafVersion, TaprootBuilder};
+use bitcoin::{PublicKey, ScriptBuf};
 use common::hex;
+use secp256k1::{KeyPair, XOnlyPublicKey};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
-use tw_bitcoin::modules::transactions::{BRC20TransferInscription, Brc20Ticker};
+use tw_bitcoin::modules::transactions::{
+    BRC20TransferInscription, Brc20Ticker, InputClaimBuilder, OutputBuilder, TapTree, TapTreeLeaf,
+    TaprootProgram, NUMS_INTERNAL_KEY,
+};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_misc::traits::ToBytesVec;
@@ -54,9 +60,11 @@ fn coin_entry_custom_script_path() {
                 Proto::mod_Output::OutputTaprootScriptPath {
                     internal_key: alice_pubkey.to_vec().into(),
                     merkle_root: merkle_root.to_vec().into(),
+                    ..Default::default()
                 },
             ),
         }),
+        ..Default::default()
     };
 
     // Change/return transaction.
@@ -67,6 +75,7 @@ fn coin_entry_custom_script_path() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -131,6 +140,7 @@ fn coin_entry_custom_script_path() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -160,3 +170,363 @@ fn coin_entry_custom_script_path() {
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
+
+#[test]
+fn p2tr_script_path_output_with_spendable_key_path() {
+    let secp = secp256k1::Secp256k1::new();
+
+    let alice_private_key = hex("e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129");
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    // Alice is the owner of the internal key (key-path), while Bob can claim
+    // the output by revealing the script (script-path).
+    let alice_xonly = XOnlyPublicKey::from(PublicKey::from_slice(&alice_pubkey).unwrap().inner);
+    let bob_xonly = XOnlyPublicKey::from(PublicKey::from_slice(&bob_pubkey).unwrap().inner);
+
+    let leaf = ScriptBuf::builder()
+        .push_x_only_key(&bob_xonly)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(0, leaf.clone())
+        .unwrap()
+        .finalize(&secp, alice_xonly)
+        .unwrap();
+
+    let merkle_root = spend_info.merkle_root().unwrap();
+
+    let out = Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_script_path(
+                Proto::mod_Output::OutputTaprootScriptPath {
+                    internal_key: alice_pubkey.to_vec().into(),
+                    merkle_root: merkle_root.to_vec().into(),
+                    ..Default::default()
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&out).unwrap();
+
+    // The output commits to the tweaked output key.
+    let output_key = spend_info.output_key();
+    let expected = ScriptBuf::new_v1_p2tr_tweaked(output_key);
+    assert_eq!(utxo.script_pubkey.as_ref(), expected.as_bytes());
+
+    // Key-path: tweaking Alice's private key with the same merkle root
+    // produces the output key.
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let tweaked = keypair.tap_tweak(&secp, Some(merkle_root));
+    assert_eq!(
+        tweaked.to_inner().x_only_public_key().0,
+        output_key.to_inner()
+    );
+
+    // Script-path: the control block proves that the leaf is committed to
+    // by the very same output key.
+    let control_block = spend_info
+        .control_block(&(leaf.clone(), LeafVersion::TapScript))
+        .unwrap();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key.to_inner(), &leaf));
+}
+
+#[test]
+fn p2tr_script_path_output_invalid_fields() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let merkle_root = hex("a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675");
+
+    // Convenience closure.
+    let build = |internal_key: &[u8], merkle_root: &[u8]| {
+        let out = Proto::Output {
+            value: 1_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2tr_script_path(
+                    Proto::mod_Output::OutputTaprootScriptPath {
+                        internal_key: internal_key.to_vec().into(),
+                        merkle_root: merkle_root.to_vec().into(),
+                        ..Default::default()
+                    },
+                ),
+            }),
+            ..Default::default()
+        };
+
+        OutputBuilder::utxo_from_proto(&out).map_err(Proto::Error::from)
+    };
+
+    assert!(build(&alice_pubkey, &merkle_root).is_ok());
+
+    // 31-byte merkle root.
+    assert_eq!(
+        build(&alice_pubkey, &merkle_root[..31]).unwrap_err(),
+        Proto::Error::Error_invalid_taproot_root
+    );
+
+    // Invalid public key.
+    assert_eq!(
+        build(&alice_pubkey[..32], &merkle_root).unwrap_err(),
+        Proto::Error::Error_invalid_public_key
+    );
+}
+
+#[test]
+fn p2tr_script_path_output_with_nums_internal_key() {
+    let secp = secp256k1::Secp256k1::new();
+
+    // The NUMS point as documented in BIP341.
+    assert_eq!(
+        NUMS_INTERNAL_KEY.to_vec(),
+        hex("50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0")
+    );
+
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+    let bob_xonly = XOnlyPublicKey::from(PublicKey::from_slice(&bob_pubkey).unwrap().inner);
+
+    let leaf = ScriptBuf::builder()
+        .push_x_only_key(&bob_xonly)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+
+    // No internal key is provided.
+    let nums_xonly = XOnlyPublicKey::from_slice(&NUMS_INTERNAL_KEY).unwrap();
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(0, leaf.clone())
+        .unwrap()
+        .finalize(&secp, nums_xonly)
+        .unwrap();
+
+    let merkle_root = spend_info.merkle_root().unwrap();
+
+    let build = |nums_internal_key: bool| {
+        let out = Proto::Output {
+            value: 1_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2tr_script_path(
+                    Proto::mod_Output::OutputTaprootScriptPath {
+                        internal_key: Default::default(),
+                        merkle_root: merkle_root.to_vec().into(),
+                        nums_internal_key,
+                    },
+                ),
+            }),
+            ..Default::default()
+        };
+
+        OutputBuilder::utxo_from_proto(&out)
+    };
+
+    // The output commits to the NUMS point as the internal key.
+    let utxo = build(true).unwrap();
+    assert_eq!(spend_info.internal_key(), nums_xonly);
+
+    let expected = ScriptBuf::new_v1_p2tr_tweaked(spend_info.output_key());
+    assert_eq!(utxo.script_pubkey.as_ref(), expected.as_bytes());
+
+    // The script-path remains spendable.
+    let control_block = spend_info
+        .control_block(&(leaf.clone(), LeafVersion::TapScript))
+        .unwrap();
+    assert_eq!(control_block.internal_key, nums_xonly);
+    assert!(control_block.verify_taproot_commitment(
+        &secp,
+        spend_info.output_key().to_inner(),
+        &leaf
+    ));
+
+    // Without the option, the internal key is required.
+    let err = build(false).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+}
+
+#[test]
+fn p2tr_script_path_witness_with_annex() {
+    let secp = secp256k1::Secp256k1::new();
+
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_xonly = XOnlyPublicKey::from(PublicKey::from_slice(&alice_pubkey).unwrap().inner);
+
+    let leaf = ScriptBuf::builder()
+        .push_x_only_key(&alice_xonly)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(0, leaf.clone())
+        .unwrap()
+        .finalize(&secp, alice_xonly)
+        .unwrap();
+
+    let control_block = spend_info
+        .control_block(&(leaf.clone(), LeafVersion::TapScript))
+        .unwrap();
+
+    let sig = bitcoin::taproot::Signature::from_slice(&[1; 64]).unwrap();
+
+    // Without an annex.
+    let witness =
+        InputClaimBuilder::p2tr_script_path_witness(&sig, leaf.as_bytes(), &control_block, None)
+            .unwrap();
+
+    let items = witness.to_vec();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0], sig.to_vec());
+    assert_eq!(items[1], leaf.as_bytes());
+    assert_eq!(items[2], control_block.serialize());
+
+    // With an annex, which is the last element.
+    let annex = hex("50deadbeef");
+    let witness = InputClaimBuilder::p2tr_script_path_witness(
+        &sig,
+        leaf.as_bytes(),
+        &control_block,
+        Some(&annex),
+    )
+    .unwrap();
+
+    let items = witness.to_vec();
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0], sig.to_vec());
+    assert_eq!(items[1], leaf.as_bytes());
+    assert_eq!(items[2], control_block.serialize());
+    assert_eq!(items[3], annex);
+
+    // The annex must start with the annex prefix.
+    let err = InputClaimBuilder::p2tr_script_path_witness(
+        &sig,
+        leaf.as_bytes(),
+        &control_block,
+        Some(&hex("51deadbeef")),
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_taproot_annex
+    );
+}
+
+#[test]
+fn p2tr_script_path_custom_leaf_version() {
+    let secp = secp256k1::Secp256k1::new();
+
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_xonly = XOnlyPublicKey::from(PublicKey::from_slice(&alice_pubkey).unwrap().inner);
+
+    let leaf = ScriptBuf::builder()
+        .push_x_only_key(&alice_xonly)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+
+    // A future (non-Tapscript) leaf version.
+    let program = TaprootProgram::new(leaf.clone(), alice_xonly, 0xc2).unwrap();
+    assert_eq!(program.leaf_version.to_consensus(), 0xc2);
+
+    // The leaf version is encoded in the first byte of the control block,
+    // next to the parity bit of the output key.
+    let control_block = program.control_block();
+    assert_eq!(control_block.serialize()[0] & 0xfe, 0xc2);
+    assert!(control_block.verify_taproot_commitment(
+        &secp,
+        program.spend_info.output_key().to_inner(),
+        &leaf
+    ));
+
+    // The leaf version is committed to, hence the output key differs from
+    // the one of the Tapscript leaf.
+    let tapscript = TaprootProgram::new(leaf.clone(), alice_xonly, 0xc0).unwrap();
+    assert_eq!(tapscript.leaf_version, LeafVersion::TapScript);
+    assert_ne!(
+        tapscript.spend_info.output_key(),
+        program.spend_info.output_key()
+    );
+
+    // Odd leaf versions and the annex prefix are not permitted.
+    for leaf_version in [0xc1, 0x50] {
+        let err = TaprootProgram::new(leaf.clone(), alice_xonly, leaf_version).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(err),
+            Proto::Error::Error_invalid_leaf_version
+        );
+    }
+}
+
+#[test]
+fn p2tr_tap_tree_round_trip() {
+    let secp = secp256k1::Secp256k1::new();
+
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let alice_xonly = XOnlyPublicKey::from(PublicKey::from_slice(&alice_pubkey).unwrap().inner);
+
+    let checksig = ScriptBuf::builder()
+        .push_x_only_key(&alice_xonly)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    // `OP_TRUE`
+    let anyone = ScriptBuf::from_bytes(vec![0x51]);
+
+    let leaf = |depth: u8, script: &ScriptBuf| TapTreeLeaf {
+        depth,
+        leaf_version: LeafVersion::TapScript,
+        script: script.clone(),
+    };
+
+    let tree = TapTree::new(vec![leaf(1, &checksig), leaf(1, &anyone)]).unwrap();
+
+    // The Merkle root matches the one of the (upstream) Taproot builder.
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(1, checksig.clone())
+        .unwrap()
+        .add_leaf(1, anyone.clone())
+        .unwrap()
+        .finalize(&secp, alice_xonly)
+        .unwrap();
+    assert_eq!(Some(tree.merkle_root()), spend_info.merkle_root());
+
+    // `<depth> <leaf version> <script length> <script>` per leaf.
+    let serialized = tree.serialize();
+    assert_eq!(
+        serialized,
+        [
+            &[0x01, 0xc0, 0x22][..],
+            checksig.as_bytes(),
+            &[0x01, 0xc0, 0x01, 0x51],
+        ]
+        .concat()
+    );
+
+    let deserialized = TapTree::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, tree);
+    assert_eq!(deserialized.merkle_root(), tree.merkle_root());
+
+    // A single leaf program exports a tree at depth 0.
+    let program = TaprootProgram::new(checksig.clone(), alice_xonly, 0xc0).unwrap();
+    let tree = program.tap_tree();
+    assert_eq!(tree.leaves().len(), 1);
+    assert_eq!(Some(tree.merkle_root()), program.spend_info.merkle_root());
+
+    // Incomplete, over-complete and truncated trees are rejected.
+    for leaves in [
+        vec![leaf(1, &checksig)],
+        vec![leaf(0, &checksig), leaf(0, &anyone)],
+        vec![leaf(2, &checksig), leaf(1, &anyone)],
+    ] {
+        let err = TapTree::new(leaves).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(err),
+            Proto::Error::Error_invalid_tap_tree
+        );
+    }
+
+    let err = TapTree::deserialize(&serialized[..serialized.len() - 1]).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_tap_tree
+    );
+}
@@ -1,8 +1,10 @@
 mod common;
 
+use bitcoin::ScriptBuf;
 use common::{hex, MINER_FEE, ONE_BTC};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::OutputBuilder;
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -41,6 +43,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wpkh() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -83,6 +86,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wpkh() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -100,3 +104,32 @@ fn coin_entry_sign_input_p2pkh_output_p2wpkh() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302483045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a60121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000");
 }
+
+#[test]
+fn serialize_p2wpkh_txout() {
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    let serialized = OutputBuilder::serialize_txout(&utxo);
+
+    let expected = bitcoin::TxOut {
+        value: ONE_BTC,
+        script_pubkey: ScriptBuf::from_bytes(utxo.script_pubkey.to_vec()),
+    };
+
+    assert_eq!(serialized, bitcoin::consensus::serialize(&expected));
+    assert_eq!(
+        tw_encoding::hex::encode(&serialized, false),
+        "00e1f505000000001600140d0e1cec6c2babe8badde5e9b3dea667da90036d"
+    );
+}
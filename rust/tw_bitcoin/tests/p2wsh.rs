@@ -1,11 +1,13 @@
 mod common;
 
 use bitcoin::consensus::Encodable;
+use bitcoin::opcodes::all::OP_CHECKMULTISIG;
 use bitcoin::{PublicKey, ScriptBuf, Witness};
 use common::{hex, MINER_FEE, ONE_BTC};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
 use tw_bitcoin::modules::signer::Signer;
+use tw_bitcoin::modules::transactions::OutputBuilder;
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -51,6 +53,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wsh() {
                 ),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -94,6 +97,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wsh() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let mut signing = Proto::SigningInput {
@@ -140,3 +144,62 @@ fn coin_entry_sign_input_p2pkh_output_p2wsh() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "02000000000101c717dda7ac1d846939637c52009d5bab1012efac1a6e019dc8f53235a24c9ddd0000000000ffffffff0100e9c829010000001976a914e4c1ea86373d554b8f4efff2cfb0001ea19124d288ac0347304402201d22810b5580a49a2e73d7c4ea90754b5d70d36adb9a8f0c9cb7393da1d1d28f02207683b2e3d31a5c7e74126681f1f2a7249b7a3a918d5890ef69b94bd3bb4fb9300121037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf1976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000");
 }
+
+// Convenience function: builds a P2WSH output for the given witness script.
+fn p2wsh_output_from_script(
+    script: &[u8],
+) -> tw_bitcoin::Result<Proto::mod_PreSigningOutput::TxOut<'static>> {
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wsh(Proto::mod_Output::OutputRedeemScriptOrHash {
+                variant: ProtoOutputRedeemScriptOrHashBuilder::redeem_script(script.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    OutputBuilder::utxo_from_proto(&output)
+}
+
+#[test]
+fn p2wsh_output_witness_script_validation() {
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    // A valid 2-of-2 multisig script.
+    let multisig = ScriptBuf::builder()
+        .push_int(2)
+        .push_key(&PublicKey::from_slice(&alice_pubkey).unwrap())
+        .push_key(&PublicKey::from_slice(&bob_pubkey).unwrap())
+        .push_int(2)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+
+    let utxo = p2wsh_output_from_script(multisig.as_bytes()).unwrap();
+    assert_eq!(
+        utxo.script_pubkey,
+        ScriptBuf::new_v0_p2wsh(&multisig.wscript_hash()).as_bytes()
+    );
+
+    // Empty script.
+    let err = p2wsh_output_from_script(&[]).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_witness_redeem_script
+    );
+
+    // Oversized script (3601 bytes of `OP_TRUE`).
+    let err = p2wsh_output_from_script(&[0x51; 3601]).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_witness_redeem_script
+    );
+
+    // Truncated push, `OP_PUSHBYTES_32` followed by a single byte.
+    let err = p2wsh_output_from_script(&[0x20, 0x00]).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_witness_redeem_script
+    );
+}
@@ -0,0 +1,18 @@
+use tw_bitcoin::modules::transactions::OutputBuilder;
+
+#[test]
+fn rbf_sequence() {
+    assert_eq!(OutputBuilder::rbf_sequence(), 0xfffffffd);
+    assert!(OutputBuilder::is_rbf_signaled(OutputBuilder::rbf_sequence()));
+}
+
+#[test]
+fn is_rbf_signaled() {
+    assert!(OutputBuilder::is_rbf_signaled(0));
+    assert!(OutputBuilder::is_rbf_signaled(1));
+    assert!(OutputBuilder::is_rbf_signaled(0xfffffffd));
+
+    // Final and locktime-only sequence numbers do not signal RBF.
+    assert!(!OutputBuilder::is_rbf_signaled(0xfffffffe));
+    assert!(!OutputBuilder::is_rbf_signaled(0xffffffff));
+}
@@ -5,6 +5,7 @@ use common::hex;
 use secp256k1::XOnlyPublicKey;
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{BitcoinNetwork, OutputBuilder};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -44,6 +45,7 @@ fn send_to_p2sh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address.to_string().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -104,6 +106,7 @@ fn send_to_p2pkh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address_string.as_str().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -165,6 +168,7 @@ fn send_to_p2wsh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address.to_string().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -225,6 +229,7 @@ fn send_to_p2wpkh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address_string.as_str().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -288,6 +293,7 @@ fn send_to_p2tr_key_path_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address_string.as_str().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -314,3 +320,285 @@ fn send_to_p2tr_key_path_address() {
     assert!(tx.outputs[0].taproot_payload.is_empty());
     assert!(tx.outputs[0].control_block.is_empty());
 }
+
+#[test]
+fn output_from_address_or_script() {
+    // `OP_RETURN <"hello">`
+    let raw_script = "6a0568656c6c6f";
+
+    // A valid address is always treated as an address.
+    let output = OutputBuilder::output_from_address_or_script(
+        1_000,
+        "19prEapJCTF3zAS2ofreXyQhcnDscuXxbd",
+        true,
+    )
+    .unwrap();
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    assert_eq!(
+        utxo.script_pubkey,
+        hex("76a91460cda7b50f14c152d7401c28ae773c698db9237388ac")
+    );
+
+    // A hex-encoded script is accepted if explicitly enabled.
+    let output = OutputBuilder::output_from_address_or_script(0, raw_script, true).unwrap();
+    assert_eq!(
+        output.to_recipient,
+        ProtoOutputRecipient::custom_script_pubkey(hex(raw_script).into())
+    );
+
+    let err = OutputBuilder::output_from_address_or_script(0, raw_script, false).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_bad_address_recipient
+    );
+
+    // A mistyped address is neither an address nor a script.
+    for allow_raw_script in [false, true] {
+        let err = OutputBuilder::output_from_address_or_script(
+            1_000,
+            "19prEapJCTF3zAS2ofreXyQhcnDscuXxbe",
+            allow_raw_script,
+        )
+        .unwrap_err();
+        assert_eq!(
+            Proto::Error::from(err),
+            Proto::Error::Error_invalid_address_checksum
+        );
+    }
+}
+
+#[test]
+fn detect_address_network() {
+    let network =
+        OutputBuilder::detect_network("bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr").unwrap();
+    assert_eq!(network, bitcoin::Network::Bitcoin);
+
+    let network = OutputBuilder::detect_network("19prEapJCTF3zAS2ofreXyQhcnDscuXxbd").unwrap();
+    assert_eq!(network, bitcoin::Network::Bitcoin);
+
+    let network =
+        OutputBuilder::detect_network("tb1qvrx60dg0znq4946qrs52uaeudxxmjgmn67shys").unwrap();
+    assert_eq!(network, bitcoin::Network::Testnet);
+
+    let network =
+        OutputBuilder::detect_network("bcrt1qvrx60dg0znq4946qrs52uaeudxxmjgmnchf6ne").unwrap();
+    assert_eq!(network, bitcoin::Network::Regtest);
+
+    // Base58 addresses of testnet and regtest share the same prefix.
+    let err = OutputBuilder::detect_network("mpLoXduH1UgJmGueXEq2Mtd2UmpaUxuiXd").unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_ambiguous_address_network
+    );
+}
+
+#[test]
+fn oversized_or_non_ascii_address() {
+    // A multi-megabyte string is rejected before parsing.
+    let huge = "bc1q".repeat(1_000_000);
+    let start = std::time::Instant::now();
+    let err = OutputBuilder::output_from_address_or_script(1_000, &huge, false).unwrap_err();
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_bad_address_recipient
+    );
+
+    // Non-ASCII input.
+    let err = OutputBuilder::output_from_address_or_script(
+        1_000,
+        "bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr\u{20ac}",
+        false,
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_bad_address_recipient
+    );
+}
+
+#[test]
+fn parse_testnet4_address() {
+    // Testnet4 uses the same `tb1` prefix as testnet3.
+    let addr = "tb1qvrx60dg0znq4946qrs52uaeudxxmjgmn67shys";
+
+    let testnet4 =
+        OutputBuilder::output_from_address_for_network(1_000, addr, BitcoinNetwork::Testnet4)
+            .unwrap();
+    let testnet =
+        OutputBuilder::output_from_address_for_network(1_000, addr, BitcoinNetwork::Testnet)
+            .unwrap();
+    assert_eq!(testnet4, testnet);
+
+    let utxo = OutputBuilder::utxo_from_proto(&testnet4).unwrap();
+    assert_eq!(
+        utxo.script_pubkey,
+        hex("001460cda7b50f14c152d7401c28ae773c698db92373")
+    );
+
+    // The magic still distinguishes the networks.
+    assert_ne!(
+        BitcoinNetwork::Testnet4.magic(),
+        BitcoinNetwork::Testnet.magic()
+    );
+
+    let err = OutputBuilder::output_from_address_for_network(1_000, addr, BitcoinNetwork::Bitcoin)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_address_wrong_network
+    );
+}
+
+#[test]
+fn all_addresses_of_pubkey() {
+    let pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let addresses = OutputBuilder::all_addresses(&pubkey, bitcoin::Network::Bitcoin).unwrap();
+    assert_eq!(addresses.p2pkh, "19prEapJCTF3zAS2ofreXyQhcnDscuXxbd");
+    assert_eq!(
+        addresses.p2wpkh,
+        "bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr"
+    );
+    assert_eq!(addresses.p2sh_p2wpkh, "3AJ2iC7F1dzNYAbHzLCNrwjmF3qHWohVb9");
+    assert_eq!(
+        addresses.p2tr,
+        "bc1pmfzznlyuyukkjwpmtjlvw5ndnlw6x0cfyr2x3h3kkw2p8afmkmgsl5l3ad"
+    );
+
+    // Uncompressed public keys are not supported.
+    let uncompressed = hex("048d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f2c2ef1572b87738c732c66d4a3770d41bb1bda428ce96b0c9829243a0a6a96ae");
+    let err = OutputBuilder::all_addresses(&uncompressed, bitcoin::Network::Bitcoin).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+}
+
+#[test]
+fn output_from_p2wsh_address_literal() {
+    // P2WSH test vector of BIP173.
+    let address = "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3";
+
+    let output = OutputBuilder::output_from_address_or_script(1_000, address, false).unwrap();
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+
+    // OP_0 <32-byte witness script hash>
+    assert_eq!(
+        utxo.script_pubkey,
+        hex("00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262")
+    );
+}
+
+#[test]
+fn output_from_address_errors() {
+    let error = |address: &str| {
+        let err = OutputBuilder::output_from_address_or_script(1_000, address, false).unwrap_err();
+        Proto::Error::from(err)
+    };
+
+    // Corrupted bech32 checksum.
+    assert_eq!(
+        error("bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctyls"),
+        Proto::Error::Error_invalid_address_checksum
+    );
+
+    // Corrupted base58 checksum.
+    assert_eq!(
+        error("19prEapJCTF3zAS2ofreXyQhcnDscuXxbe"),
+        Proto::Error::Error_invalid_address_checksum
+    );
+
+    // Valid testnet address, but mainnet is expected.
+    assert_eq!(
+        error("tb1qvrx60dg0znq4946qrs52uaeudxxmjgmn67shys"),
+        Proto::Error::Error_address_wrong_network
+    );
+
+    // Malformed address.
+    assert_eq!(
+        error("not-an-address"),
+        Proto::Error::Error_bad_address_recipient
+    );
+}
+
+#[test]
+fn address_from_built_script() {
+    for address in [
+        "19prEapJCTF3zAS2ofreXyQhcnDscuXxbd",
+        "3AJ2iC7F1dzNYAbHzLCNrwjmF3qHWohVb9",
+        "bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr",
+        "bc1pmfzznlyuyukkjwpmtjlvw5ndnlw6x0cfyr2x3h3kkw2p8afmkmgsl5l3ad",
+    ] {
+        let output = OutputBuilder::output_from_address_or_script(1_000, address, false).unwrap();
+        let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+
+        let reversed =
+            OutputBuilder::address_from_script(&utxo.script_pubkey, bitcoin::Network::Bitcoin)
+                .unwrap();
+        assert_eq!(reversed, address);
+    }
+
+    // The same script on testnet.
+    let output = OutputBuilder::output_from_address_or_script(
+        1_000,
+        "bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr",
+        false,
+    )
+    .unwrap();
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    let reversed =
+        OutputBuilder::address_from_script(&utxo.script_pubkey, bitcoin::Network::Testnet).unwrap();
+    assert_eq!(reversed, "tb1qvrx60dg0znq4946qrs52uaeudxxmjgmn67shys");
+
+    // `OP_RETURN <"hello">` has no address.
+    let err = OutputBuilder::address_from_script(&hex("6a0568656c6c6f"), bitcoin::Network::Bitcoin)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_unsupported_address_recipient
+    );
+}
+
+#[test]
+fn script_matches_address() {
+    let addresses = [
+        "19prEapJCTF3zAS2ofreXyQhcnDscuXxbd",
+        "3AJ2iC7F1dzNYAbHzLCNrwjmF3qHWohVb9",
+        "bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr",
+        "bc1pmfzznlyuyukkjwpmtjlvw5ndnlw6x0cfyr2x3h3kkw2p8afmkmgsl5l3ad",
+    ];
+
+    let scripts: Vec<_> = addresses
+        .iter()
+        .map(|address| {
+            let output =
+                OutputBuilder::output_from_address_or_script(1_000, address, false).unwrap();
+            OutputBuilder::utxo_from_proto(&output)
+                .unwrap()
+                .script_pubkey
+                .to_vec()
+        })
+        .collect();
+
+    for (i, script) in scripts.iter().enumerate() {
+        for (j, address) in addresses.iter().enumerate() {
+            let matches =
+                OutputBuilder::script_matches_address(script, address, bitcoin::Network::Bitcoin)
+                    .unwrap();
+            assert_eq!(matches, i == j);
+        }
+    }
+
+    // The address must belong to the given network.
+    let err = OutputBuilder::script_matches_address(
+        &scripts[2],
+        "bc1qvrx60dg0znq4946qrs52uaeudxxmjgmnsctylr",
+        bitcoin::Network::Testnet,
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_address_wrong_network
+    );
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "silent-payments")]
+
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::transactions::OutputBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+// "Simple send: two inputs" test vector of BIP352, paying to
+// sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv
+const SCAN_KEY: &str = "0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4";
+const SPEND_KEY: &str = "025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36";
+
+const FIRST_TXID: &str = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+const FIRST_PRIVATE_KEY: &str = "eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1";
+const FIRST_PUBKEY: &str = "025a1e61f898173040e20616d43e9f496fba90338a39faa1ed98fcbaeee4dd9be5";
+
+const SECOND_TXID: &str = "a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d";
+const SECOND_PRIVATE_KEY: &str = "93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16";
+const SECOND_PUBKEY: &str = "03bd85685d03d111699b15d046319febe77f8de5286e9e512703cdee1bf3be3792";
+
+const EXPECTED_OUTPUT_KEY: &str =
+    "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1";
+
+fn outpoint(txid: &str, vout: u32) -> Proto::mod_Output::OutPoint<'static> {
+    Proto::mod_Output::OutPoint {
+        txid: hex(txid).into_iter().rev().collect::<Vec<u8>>().into(),
+        vout,
+    }
+}
+
+fn silent_payment_output(
+    payment: Proto::mod_Output::OutputSilentPayment<'static>,
+) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::silent_payment(payment),
+        }),
+        ..Default::default()
+    }
+}
+
+fn reference_payment() -> Proto::mod_Output::OutputSilentPayment<'static> {
+    Proto::mod_Output::OutputSilentPayment {
+        recipient_scan_key: hex(SCAN_KEY).into(),
+        recipient_spend_key: hex(SPEND_KEY).into(),
+        input_pubkeys: vec![hex(FIRST_PUBKEY).into(), hex(SECOND_PUBKEY).into()],
+        input_private_keys: vec![
+            hex(FIRST_PRIVATE_KEY).into(),
+            hex(SECOND_PRIVATE_KEY).into(),
+        ],
+        outpoints: vec![outpoint(FIRST_TXID, 0), outpoint(SECOND_TXID, 0)],
+        k: 0,
+    }
+}
+
+#[test]
+fn silent_payment_reference_vector() {
+    let utxo = OutputBuilder::utxo_from_proto(&silent_payment_output(reference_payment())).unwrap();
+
+    // `OP_1 <output key>`
+    let expected = hex(&format!("5120{EXPECTED_OUTPUT_KEY}"));
+    assert_eq!(utxo.script_pubkey.as_ref(), expected.as_slice());
+
+    // The order of the outpoints does not matter.
+    let mut payment = reference_payment();
+    payment.outpoints.reverse();
+    let utxo = OutputBuilder::utxo_from_proto(&silent_payment_output(payment)).unwrap();
+    assert_eq!(utxo.script_pubkey.as_ref(), expected.as_slice());
+
+    // A different index results in a different output.
+    let mut payment = reference_payment();
+    payment.k = 1;
+    let utxo = OutputBuilder::utxo_from_proto(&silent_payment_output(payment)).unwrap();
+    assert_ne!(utxo.script_pubkey.as_ref(), expected.as_slice());
+}
+
+#[test]
+fn silent_payment_invalid() {
+    // The private key does not match the public key.
+    let mut payment = reference_payment();
+    payment.input_pubkeys.swap(0, 1);
+    let err = OutputBuilder::utxo_from_proto(&silent_payment_output(payment)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_silent_payment
+    );
+
+    // Duplicate outpoints.
+    let mut payment = reference_payment();
+    payment.outpoints = vec![outpoint(FIRST_TXID, 0), outpoint(FIRST_TXID, 0)];
+    let err = OutputBuilder::utxo_from_proto(&silent_payment_output(payment)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_silent_payment
+    );
+
+    // No outpoints.
+    let mut payment = reference_payment();
+    payment.outpoints.clear();
+    let err = OutputBuilder::utxo_from_proto(&silent_payment_output(payment)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_silent_payment
+    );
+
+    // Invalid recipient key.
+    let mut payment = reference_payment();
+    payment.recipient_scan_key = hex(&SCAN_KEY[2..]).into();
+    let err = OutputBuilder::utxo_from_proto(&silent_payment_output(payment)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+}
@@ -41,6 +41,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_p2pkh_script(
                 to_address: ProtoPubkeyOrHash::pubkey(recipient.to_bytes().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -82,6 +83,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_p2wpkh_script(
                 to_address: ProtoPubkeyOrHash::pubkey(recipient.to_bytes().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -120,6 +122,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_p2tr_key_path_script(
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(recipient.to_bytes().into()),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -179,6 +182,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_brc20_transfer_inscription(
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -237,9 +241,11 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_nft_inscription(
                     inscribe_to: recipient.to_bytes().into(),
                     mime_type: mime_type.into(),
                     payload: payload.into(),
+                    ..Default::default()
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(